@@ -0,0 +1,144 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ss_graph_rs::graph::Graph;
+use ss_graph_rs::search::Search;
+
+// Pseudo-random pairs of node numbers below `nodes`, the same for a given seed on every run.
+fn random_pairs(seed: u64, nodes: u64, count: usize) -> Vec<(u32, u32)> {
+    let mut state = seed;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((state >> 33) % nodes) as u32
+    };
+    (0..count).map(|_| (next(), next())).collect()
+}
+
+fn name(node: u32) -> String {
+    format!("node-{node}")
+}
+
+// Sparse directed graph with string nodes, like a road or dependency network.
+fn sparse_graph() -> Graph<String> {
+    let mut graph = Graph::new(Some(true));
+    for (x, y) in random_pairs(7, 20_000, 60_000) {
+        graph.add_edge(name(x), name(y));
+    }
+    graph
+}
+
+// Many short bounded queries. Pruning with the index of incoming edges, which a FrozenGraph
+// always has, is what makes the difference here; the graph fits in cache either way.
+fn bench_short_queries(c: &mut Criterion) {
+    let graph = sparse_graph();
+    let mut indexed = graph.clone();
+    indexed.enable_reverse_index();
+    let frozen = graph.clone().freeze();
+    let queries: Vec<_> = random_pairs(11, 20_000, 100)
+        .into_iter()
+        .map(|(start, end)| (name(start), name(end)))
+        .collect();
+
+    let mut group = c.benchmark_group("100 bounded queries, 20k nodes");
+    group.bench_function("Graph", |b| {
+        b.iter(|| {
+            for (start, end) in &queries {
+                black_box(graph.count_paths_with_max_steps(start.clone(), end.clone(), 6));
+            }
+        })
+    });
+    group.bench_function("Graph with reverse index", |b| {
+        b.iter(|| {
+            for (start, end) in &queries {
+                black_box(indexed.count_paths_with_max_steps(start.clone(), end.clone(), 6));
+            }
+        })
+    });
+    group.bench_function("FrozenGraph", |b| {
+        b.iter(|| {
+            for (start, end) in &queries {
+                black_box(frozen.count_paths_with_max_steps(start.clone(), end.clone(), 6));
+            }
+        })
+    });
+    group.finish();
+}
+
+// Directed grid where every node links right and down, so the corners are joined by
+// C(2 * (side - 1), side - 1) paths.
+fn grid(side: u32) -> Graph<String> {
+    let mut graph = Graph::new(Some(true));
+    let name = |x: u32, y: u32| format!("{x},{y}");
+    for x in 0..side {
+        for y in 0..side {
+            if x + 1 < side {
+                graph.add_edge(name(x, y), name(x + 1, y));
+            }
+            if y + 1 < side {
+                graph.add_edge(name(x, y), name(x, y + 1));
+            }
+        }
+    }
+    graph
+}
+
+// One long enumeration on a graph small enough to stay in cache, where both layouts
+// perform alike.
+fn bench_enumeration(c: &mut Criterion) {
+    let graph = grid(9);
+    let frozen = graph.clone().freeze();
+    let (start, end) = ("0,0".to_string(), "8,8".to_string());
+
+    let mut group = c.benchmark_group("count_paths_with_max_steps, 9x9 grid");
+    group.bench_function("Graph", |b| {
+        b.iter(|| graph.count_paths_with_max_steps(black_box(start.clone()), end.clone(), 17))
+    });
+    group.bench_function("FrozenGraph", |b| {
+        b.iter(|| frozen.count_paths_with_max_steps(black_box(start.clone()), end.clone(), 17))
+    });
+    group.finish();
+}
+
+// A traversal of a graph far larger than the cache, where the compact edge array of a
+// FrozenGraph saves memory traffic over the separate adjacency list of each node.
+fn bench_large_traversal(c: &mut Criterion) {
+    let mut graph = Graph::new(Some(true));
+    for (x, y) in random_pairs(13, 1_000_000, 4_000_000) {
+        graph.add_edge(x, y);
+    }
+    let frozen = graph.clone().freeze();
+
+    let mut group = c.benchmark_group("bfs, 1M nodes and 4M edges");
+    group.sample_size(10);
+    group.bench_function("Graph", |b| b.iter(|| graph.bfs(black_box(1)).count()));
+    group.bench_function("FrozenGraph", |b| {
+        b.iter(|| frozen.bfs(black_box(1)).count())
+    });
+    group.finish();
+}
+
+// A short query on a long chain, which should not cost time proportional to the graph.
+fn bench_local_query(c: &mut Criterion) {
+    let mut graph = Graph::new(Some(true));
+    for i in 0..1_000_000u32 {
+        graph.add_edge(i, i + 1);
+    }
+
+    let mut group = c.benchmark_group("short query, 1M-node chain");
+    group.bench_function("paths_iter().next()", |b| {
+        b.iter(|| graph.paths_iter(black_box(0), 1).next())
+    });
+    group.bench_function("count_paths_with_max_steps", |b| {
+        b.iter(|| graph.count_paths_with_max_steps(black_box(0), 1, 2))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_short_queries,
+    bench_enumeration,
+    bench_large_traversal,
+    bench_local_query
+);
+criterion_main!(benches);
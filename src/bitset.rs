@@ -0,0 +1,54 @@
+// Fixed-size set of node ids, one bit per id.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    // Create an empty set that can hold ids below `len`.
+    pub(crate) fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    // Add an id. Returns true if it was not already present.
+    pub(crate) fn insert(&mut self, id: u32) -> bool {
+        let (word, bit) = Self::locate(id);
+        let was_present = self.words[word] & bit != 0;
+        self.words[word] |= bit;
+        !was_present
+    }
+
+    pub(crate) fn remove(&mut self, id: u32) {
+        let (word, bit) = Self::locate(id);
+        self.words[word] &= !bit;
+    }
+
+    pub(crate) fn contains(&self, id: u32) -> bool {
+        let (word, bit) = Self::locate(id);
+        self.words.get(word).is_some_and(|word| word & bit != 0)
+    }
+
+    fn locate(id: u32) -> (usize, u64) {
+        (id as usize / 64, 1 << (id % 64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut set = BitSet::new(130);
+        assert!(set.insert(0));
+        assert!(set.insert(129));
+        assert!(!set.insert(129));
+        assert!(set.contains(0) && set.contains(129));
+        assert!(!set.contains(64));
+        assert!(!set.contains(1000));
+        set.remove(129);
+        assert!(!set.contains(129));
+    }
+}
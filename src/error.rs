@@ -0,0 +1,36 @@
+use std::error::Error;
+use std::fmt;
+
+// Errors reported by graph operations, naming the nodes involved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphError<T> {
+    // The graph's SelfLoops policy rejects an edge from this node to itself.
+    SelfLoop(T),
+    // A shortest-path search that needs non-negative weights found a negative edge between
+    // these two nodes in the graph.
+    NegativeWeight(T, T),
+    // A shortest-path search found a cycle whose weights add up to less than zero, so routes
+    // through it have no cheapest cost. The nodes are in edge order, the first repeated at the end.
+    NegativeCycle(Vec<T>),
+}
+
+impl<T: fmt::Debug> fmt::Display for GraphError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::SelfLoop(node) => {
+                write!(f, "self-loop on {node:?} is rejected by the graph")
+            }
+            GraphError::NegativeWeight(vector_x, vector_y) => {
+                write!(
+                    f,
+                    "edge from {vector_x:?} to {vector_y:?} has a negative weight"
+                )
+            }
+            GraphError::NegativeCycle(cycle) => {
+                write!(f, "negative cycle through {cycle:?}")
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for GraphError<T> {}
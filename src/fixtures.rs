@@ -0,0 +1,30 @@
+// Graphs shared by the tests of several modules.
+
+use crate::graph::Graph;
+
+// Graph with `edges` edges between pseudo-random nodes below `nodes`. A linear congruential
+// generator picks the endpoints, so the same seed always gives the same graph.
+pub(crate) fn random_graph(seed: u64, nodes: u64, edges: usize, is_directed: bool) -> Graph<u64> {
+    let mut state = seed;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) % nodes
+    };
+    let mut graph = Graph::new(Some(is_directed));
+    for _ in 0..edges {
+        let (x, y) = (next(), next());
+        graph.add_edge(x, y);
+    }
+    graph
+}
+
+// Two routes from 1 to 6, through 2-4 or 3-5, with a shortcut 2-5 between them.
+pub(crate) fn two_routes(is_directed: bool) -> Graph<i32> {
+    let mut graph = Graph::new(Some(is_directed));
+    for (x, y) in [(1, 2), (2, 4), (4, 6), (1, 3), (3, 5), (5, 6), (2, 5)] {
+        graph.add_edge(x, y);
+    }
+    graph
+}
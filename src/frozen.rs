@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::graph::{Graph, NodeId};
+use crate::paths::PathsIter;
+use crate::view::View;
+
+// Read-only graph in compressed sparse row form, built by Graph::freeze.
+// The edges of all nodes sit in one contiguous array, which takes less memory than the separate
+// adjacency list of each node in a Graph and pays off on graphs too large for the cache.
+// The index of incoming edges is always built, so bounded searches can prune from the end.
+#[derive(Clone)]
+pub struct FrozenGraph<T: Eq + Hash + Clone, E = (), N = ()> {
+    pub(crate) is_directed: bool,
+    pub(crate) ids: HashMap<T, NodeId>,
+    pub(crate) nodes: Vec<T>,
+    // Edges out of node `id` are at offsets[id]..offsets[id + 1] in targets and edge_data.
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+    edge_data: Vec<E>,
+    // Edges into each node of a directed graph, laid out the same way.
+    // Undirected graphs store every edge both ways, so they use the outgoing edges instead.
+    reverse_offsets: Vec<usize>,
+    sources: Vec<NodeId>,
+    pub(crate) node_data: Vec<Option<N>>,
+    // Number of stored edge directions with a negative weight, if the graph it was frozen
+    // from kept count.
+    pub(crate) negative_edges: Option<usize>,
+}
+
+impl<T, E, N> Graph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    // Turn the graph into a read-only FrozenGraph that answers the same path queries faster.
+    pub fn freeze(self) -> FrozenGraph<T, E, N> {
+        // Removed nodes leave holes in the ids, so number the remaining nodes again.
+        let mut new_ids = vec![NodeId::MAX; self.id_bound()];
+        let mut nodes = Vec::with_capacity(self.node_count());
+        for (id, node) in self.nodes.into_iter().enumerate() {
+            if let Some(node) = node {
+                new_ids[id] = nodes.len() as NodeId;
+                nodes.push(node);
+            }
+        }
+        let live = |id: &usize| new_ids[*id] != NodeId::MAX;
+
+        let mut offsets = Vec::with_capacity(nodes.len() + 1);
+        let mut targets = Vec::new();
+        let mut edge_data = Vec::new();
+        offsets.push(0);
+        for (_, neighbors) in self
+            .adjacency_list
+            .into_iter()
+            .enumerate()
+            .filter(|(id, _)| live(id))
+        {
+            for (neighbor, data) in neighbors {
+                targets.push(new_ids[neighbor as usize]);
+                edge_data.push(data);
+            }
+            offsets.push(targets.len());
+        }
+
+        let negative_edges = self
+            .is_negative
+            .map(|is_negative| edge_data.iter().filter(|data| is_negative(data)).count());
+        let (reverse_offsets, sources) = if self.is_directed {
+            reverse_edges(&offsets, &targets)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        FrozenGraph {
+            is_directed: self.is_directed,
+            ids: self
+                .ids
+                .into_iter()
+                .map(|(node, id)| (node, new_ids[id as usize]))
+                .collect(),
+            nodes,
+            offsets,
+            targets,
+            edge_data,
+            reverse_offsets,
+            sources,
+            node_data: self
+                .node_data
+                .into_iter()
+                .enumerate()
+                .filter(|(id, _)| live(id))
+                .map(|(_, data)| data)
+                .collect(),
+            negative_edges,
+        }
+    }
+}
+
+// Group the edges by target with a counting sort, giving the sources of each node's incoming edges.
+fn reverse_edges(offsets: &[usize], targets: &[NodeId]) -> (Vec<usize>, Vec<NodeId>) {
+    let mut reverse_offsets = vec![0; offsets.len()];
+    for target in targets {
+        reverse_offsets[*target as usize + 1] += 1;
+    }
+    for id in 1..reverse_offsets.len() {
+        reverse_offsets[id] += reverse_offsets[id - 1];
+    }
+    let mut next = reverse_offsets.clone();
+    let mut sources = vec![0; targets.len()];
+    for (source, range) in offsets.windows(2).enumerate() {
+        for target in &targets[range[0]..range[1]] {
+            sources[next[*target as usize]] = source as NodeId;
+            next[*target as usize] += 1;
+        }
+    }
+    (reverse_offsets, sources)
+}
+
+impl<T, E, N> FrozenGraph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    // Read access for the searches shared with Graph.
+    pub(crate) fn view(&self) -> View<'_, T, E, N> {
+        View::Frozen(self)
+    }
+
+    pub(crate) fn id(&self, node: &T) -> Option<NodeId> {
+        self.ids.get(node).copied()
+    }
+
+    pub(crate) fn neighbor_ids(&self, id: NodeId) -> &[NodeId] {
+        &self.targets[self.offsets[id as usize]..self.offsets[id as usize + 1]]
+    }
+
+    pub(crate) fn edge_data_from(&self, id: NodeId) -> &[E] {
+        &self.edge_data[self.offsets[id as usize]..self.offsets[id as usize + 1]]
+    }
+
+    pub(crate) fn predecessor_ids(&self, id: NodeId) -> &[NodeId] {
+        if !self.is_directed {
+            return self.neighbor_ids(id);
+        }
+        &self.sources[self.reverse_offsets[id as usize]..self.reverse_offsets[id as usize + 1]]
+    }
+
+    // Get the data of the edge between two nodes.
+    pub fn edge(&self, vector_x: &T, vector_y: &T) -> Option<&E> {
+        let id_x = self.id(vector_x)?;
+        let id_y = self.id(vector_y)?;
+        let position = self.neighbor_ids(id_x).iter().position(|id| *id == id_y)?;
+        Some(&self.edge_data[self.offsets[id_x as usize] + position])
+    }
+
+    // Iterate over every edge with its data. An undirected edge is yielded once.
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T, &E)> {
+        self.offsets
+            .windows(2)
+            .enumerate()
+            .flat_map(|(id, range)| (range[0]..range[1]).map(move |position| (id, position)))
+            .filter(|(id, position)| self.is_directed || *id <= self.targets[*position] as usize)
+            .map(|(id, position)| {
+                let target = self.targets[position] as usize;
+                (
+                    &self.nodes[id],
+                    &self.nodes[target],
+                    &self.edge_data[position],
+                )
+            })
+    }
+
+    // Get the data attached to a node.
+    pub fn node_data(&self, node: &T) -> Option<&N> {
+        self.node_data[self.id(node)? as usize].as_ref()
+    }
+
+    // Check whether a node is in the graph.
+    pub fn contains_node(&self, node: &T) -> bool {
+        self.ids.contains_key(node)
+    }
+
+    // Iterate over every node, including sinks and isolated nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter()
+    }
+
+    // Iterate over the nodes with an edge from `node`.
+    pub fn neighbors<'a>(&'a self, node: &T) -> impl Iterator<Item = &'a T> + 'a {
+        self.id(node)
+            .into_iter()
+            .flat_map(|id| self.neighbor_ids(id))
+            .map(|neighbor| &self.nodes[*neighbor as usize])
+    }
+
+    // Iterate over the nodes with an edge to `node`.
+    pub fn predecessors<'a>(&'a self, node: &T) -> impl Iterator<Item = &'a T> + 'a {
+        self.id(node)
+            .into_iter()
+            .flat_map(|id| self.predecessor_ids(id))
+            .map(|predecessor| &self.nodes[*predecessor as usize])
+    }
+
+    // Count the nodes, including sinks and isolated nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Count the edges. An undirected edge is counted once, and so is a self-loop.
+    pub fn edge_count(&self) -> usize {
+        if self.is_directed {
+            return self.targets.len();
+        }
+        let self_loops = (0..self.nodes.len() as NodeId)
+            .filter(|id| self.neighbor_ids(*id).contains(id))
+            .count();
+        (self.targets.len() + self_loops) / 2
+    }
+
+    // Check whether a node has an edge to itself.
+    pub fn has_self_loop(&self, node: &T) -> bool {
+        self.edge(node, node).is_some()
+    }
+
+    // Count the edges into `node`. In an undirected graph this is the degree.
+    pub fn in_degree(&self, node: &T) -> usize {
+        if !self.is_directed {
+            return self.degree(node);
+        }
+        self.predecessors(node).count()
+    }
+
+    // Count the edges out of `node`. In an undirected graph this is the degree.
+    pub fn out_degree(&self, node: &T) -> usize {
+        if !self.is_directed {
+            return self.degree(node);
+        }
+        self.neighbors(node).count()
+    }
+
+    // Count the edge ends at `node`. A self-loop has both of its ends there, so it counts twice.
+    pub fn degree(&self, node: &T) -> usize {
+        if self.is_directed {
+            return self.in_degree(node) + self.out_degree(node);
+        }
+        self.neighbors(node).count() + usize::from(self.has_self_loop(node))
+    }
+
+    // Lazily iterate over all paths between two nodes.
+    pub fn paths_iter(&self, start: T, end: T) -> PathsIter<'_, T, E, N> {
+        self.view().paths_iter(start, end)
+    }
+
+    // Use depth-first search to find all paths between two nodes
+    pub fn find_all_paths(&self, start: T, end: T) -> Vec<Vec<T>> {
+        self.paths_iter(start, end).collect()
+    }
+
+    // Use depth-first search to find all paths between two nodes with max steps limit.
+    // The limit is counted in nodes, so a path of `max_steps` nodes is still accepted.
+    pub fn find_paths_with_max_steps(&self, start: T, end: T, max_steps: usize) -> Vec<Vec<T>> {
+        self.view()
+            .paths_iter_with_max_steps(start, end, max_steps)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::fixtures::random_graph;
+    use crate::search::Search;
+
+    #[test]
+    fn test_freeze_matches_graph() {
+        for seed in 0..20 {
+            let mut graph = random_graph(seed, 10, 25, seed % 2 == 0);
+            graph.remove_node(&(seed % 10));
+            let frozen = graph.clone().freeze();
+            assert_eq!(frozen.node_count(), graph.node_count());
+            assert_eq!(frozen.edge_count(), graph.edge_count());
+            for node in graph.nodes() {
+                assert_eq!(frozen.in_degree(node), graph.in_degree(node));
+                assert_eq!(frozen.degree(node), graph.degree(node));
+            }
+            for (start, end) in [(0, 9), (1, 5), (3, 3)] {
+                assert_eq!(
+                    frozen.find_all_paths(start, end),
+                    graph.find_all_paths(start, end)
+                );
+                assert_eq!(
+                    frozen.count_paths(start, end),
+                    graph.count_paths(start, end)
+                );
+                assert_eq!(
+                    frozen.find_paths_with_max_steps(start, end, 4),
+                    graph.find_paths_with_max_steps(start, end, 4)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_frozen_graph() {
+        let mut graph = Graph::new_with_data(Some(true));
+        graph.add_edge_with(1, 2, "a");
+        graph.add_edge_with(2, 4, "b");
+        graph.add_edge_with(1, 3, "c");
+        graph.add_edge_with(3, 4, "d");
+        graph.add_edge_with(4, 4, "e");
+        graph.add_node(5);
+        graph.set_node_data(3, 30);
+        let frozen = graph.freeze();
+
+        assert_eq!(frozen.edge(&3, &4), Some(&"d"));
+        assert_eq!(frozen.edge(&4, &3), None);
+        assert_eq!(frozen.edges().count(), 5);
+        assert_eq!(frozen.node_data(&3), Some(&30));
+        assert_eq!(frozen.node_data(&5), None);
+        assert!(frozen.contains_node(&5));
+        let mut predecessors: Vec<_> = frozen.predecessors(&4).copied().collect();
+        predecessors.sort();
+        assert_eq!(predecessors, vec![2, 3, 4]);
+        assert_eq!(frozen.degree(&4), 4);
+
+        assert_eq!(
+            frozen.find_all_paths_sorted(1, 4),
+            vec![vec![1, 2, 4], vec![1, 3, 4]]
+        );
+        assert_eq!(frozen.count_paths_dag(1, 4), Some(2));
+        assert_eq!(frozen.find_all_paths(5, 5), vec![vec![5]]);
+        let paths = frozen
+            .query(1, 4)
+            .filter_nodes(|_, data| data.is_none())
+            .find_all_paths();
+        assert_eq!(paths, vec![vec![1, 2, 4]]);
+        let targets = HashSet::from([2, 3]);
+        assert_eq!(frozen.find_all_paths_between([1], &targets).len(), 2);
+        assert!(frozen.find_all_paths(4, 1).is_empty());
+        assert_eq!(
+            frozen.find_all_paths_with_edges(2, 4),
+            vec![(vec![2, 4], vec![&"b"])]
+        );
+    }
+
+    #[test]
+    fn test_frozen_weighted_paths() {
+        let mut graph = Graph::new_weighted(Some(false));
+        for (x, y, weight) in [(1, 2, 4), (2, 4, 1), (1, 3, 1), (3, 4, 2), (3, 2, 1)] {
+            graph.add_weighted_edge(x, y, weight);
+        }
+        let frozen = graph.clone().freeze();
+        assert_eq!(frozen.weight(&2, &3), Some(1));
+        assert_eq!(frozen.path_cost(&[1, 3, 2, 4]), Some(3));
+        assert_eq!(frozen.path_cost(&[1, 4]), None);
+        assert_eq!(
+            frozen.find_all_weighted_paths(1, 4),
+            graph.find_all_weighted_paths(1, 4)
+        );
+        let mut paths = frozen.find_weighted_paths_with_max_cost(1, 4, 3);
+        paths.sort();
+        assert_eq!(paths, vec![(vec![1, 3, 2, 4], 3), (vec![1, 3, 4], 3)]);
+    }
+}
@@ -1,11 +1,86 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::hash::Hash;
 
-#[derive(Clone, PartialEq, Eq)]
-pub struct Graph<T: Eq + Hash + Clone> {
-    is_directed: bool,
-    adjacency_list: HashMap<T, HashSet<T>>,
+use crate::error::GraphError;
+use crate::paths::PathsIter;
+use crate::view::View;
+use crate::weight::Weight;
+
+// Dense id a graph gives each of its nodes, used by the algorithms in place of the node itself.
+pub(crate) type NodeId = u32;
+
+// What a graph does with an edge from a node to itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SelfLoops {
+    // Keep self-loops like any other edge.
+    #[default]
+    Allow,
+    // Refuse self-loops: try_add_edge returns an error and add_edge panics.
+    Reject,
+    // Drop self-loops silently, still adding the node.
+    Ignore,
+}
+
+// A graph whose edges carry data of type `E`, such as a weight or a label,
+// and whose nodes can carry data of type `N`. Graphs without such data use `()`.
+#[derive(Clone)]
+pub struct Graph<T: Eq + Hash + Clone, E = (), N = ()> {
+    pub(crate) is_directed: bool,
+    pub(crate) self_loops: SelfLoops,
+    // Nodes are interned once on insertion, so the algorithms run on ids and only translate
+    // back to `T` for output. A removed node leaves an empty slot for the next new node.
+    pub(crate) ids: HashMap<T, NodeId>,
+    pub(crate) nodes: Vec<Option<T>>,
+    free_ids: Vec<NodeId>,
+    // Outgoing edges of each node with their data, indexed by id.
+    pub(crate) adjacency_list: Vec<Vec<(NodeId, E)>>,
+    // Position of each edge in the adjacency list of its source.
+    edge_positions: HashMap<(NodeId, NodeId), usize>,
+    // Incoming edges of a directed graph, kept only once enable_reverse_index is called.
+    pub(crate) reverse_adjacency: Option<Vec<Vec<NodeId>>>,
+    // Data of the nodes that have any, indexed by id.
+    pub(crate) node_data: Vec<Option<N>>,
+    // Tells whether an edge's weight is negative. Set by add_weighted_edge, the first point
+    // where the edge data is known to be a weight.
+    pub(crate) is_negative: Option<fn(&E) -> bool>,
+    // Number of stored edge directions with a negative weight, counted while is_negative is set,
+    // so searches that need non-negative weights can check the graph without a scan.
+    negative_edges: usize,
+}
+
+// Ids and the reverse index are internal, so graphs are equal when they hold the same nodes,
+// edges and data, whatever order they were built in.
+impl<T, E, N> PartialEq for Graph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+    E: PartialEq,
+    N: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.is_directed == other.is_directed
+            && self.self_loops == other.self_loops
+            && self.ids.len() == other.ids.len()
+            && self.edge_positions.len() == other.edge_positions.len()
+            && self.ids.iter().all(|(node, &id)| {
+                other.id(node).is_some_and(|other_id| {
+                    self.node_data[id as usize] == other.node_data[other_id as usize]
+                        && self.adjacency_list[id as usize]
+                            .iter()
+                            .all(|(neighbor, data)| {
+                                other.edge(node, self.node_at(*neighbor)) == Some(data)
+                            })
+                })
+            })
+    }
+}
+
+impl<T, E, N> Eq for Graph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+    E: Eq,
+    N: Eq,
+{
 }
 
 impl<T> Graph<T>
@@ -13,127 +88,516 @@ where
     T: Eq + Hash + Clone,
 {
     pub fn new(is_directed: Option<bool>) -> Self {
+        Self::new_with_data(is_directed)
+    }
+}
+
+impl<T, E> Graph<T, E>
+where
+    T: Eq + Hash + Clone,
+{
+    // Create a graph whose edges carry data, such as weights or labels.
+    pub fn new_weighted(is_directed: Option<bool>) -> Self {
+        Self::new_with_data(is_directed)
+    }
+}
+
+impl<T, N> Graph<T, (), N>
+where
+    T: Eq + Hash + Clone,
+{
+    // Add an edge to the graph. Both nodes are added if they are not present yet.
+    // Panics if the graph rejects self-loops and both nodes are the same.
+    pub fn add_edge(&mut self, vector_x: T, vector_y: T) {
+        self.add_edge_with(vector_x, vector_y, ());
+    }
+
+    // Add an edge to the graph, or return an error if the graph rejects it as a self-loop.
+    pub fn try_add_edge(&mut self, vector_x: T, vector_y: T) -> Result<(), GraphError<T>> {
+        self.try_add_edge_with(vector_x, vector_y, ())
+    }
+}
+
+impl<T, E, N> Graph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    // Create a graph whose edges and nodes both carry data.
+    pub fn new_with_data(is_directed: Option<bool>) -> Self {
         let is_directed = is_directed.unwrap_or(false); // default to undirected graph
         Graph {
             is_directed,
-            adjacency_list: HashMap::new(),
+            self_loops: SelfLoops::Allow,
+            ids: HashMap::new(),
+            nodes: Vec::new(),
+            free_ids: Vec::new(),
+            adjacency_list: Vec::new(),
+            edge_positions: HashMap::new(),
+            reverse_adjacency: None,
+            node_data: Vec::new(),
+            is_negative: None,
+            negative_edges: 0,
         }
     }
 
-    // Add a node to the graph.
-    pub fn add_edge(&mut self, vector_x: T, vector_y: T) {
-        self.adjacency_list
-            .entry(vector_x.clone())
-            .or_insert(HashSet::new())
-            .insert(vector_y.clone());
+    // Read access for the searches shared with FrozenGraph.
+    pub(crate) fn view(&self) -> View<'_, T, E, N> {
+        View::Hashed(self)
+    }
 
-        if !self.is_directed {
-            self.adjacency_list
-                .entry(vector_y.clone())
-                .or_insert(HashSet::new())
-                .insert(vector_x.clone());
-        }
+    // Get the id of a node.
+    pub(crate) fn id(&self, node: &T) -> Option<NodeId> {
+        self.ids.get(node).copied()
     }
 
-    // Use depth-first search to find all paths between two nodes
-    pub fn find_all_paths(&self, start: T, end: T) -> Vec<Vec<T>> {
-        let mut paths = Vec::new();
-        let mut visited = HashSet::new();
-        let mut path = Vec::new();
+    // Get the node with a given id.
+    pub(crate) fn node_at(&self, id: NodeId) -> &T {
+        self.nodes[id as usize]
+            .as_ref()
+            .expect("ids of removed nodes are never handed out")
+    }
 
-        path.push(start.clone());
-        visited.insert(start.clone());
+    // Get the data of the node with a given id.
+    pub(crate) fn node_data_at(&self, id: NodeId) -> Option<&N> {
+        self.node_data[id as usize].as_ref()
+    }
 
-        self.find_all_paths_helper(start, end, &mut visited, &mut path, &mut paths);
+    // Upper bound on node ids, for sizing tables indexed by id.
+    pub(crate) fn id_bound(&self) -> usize {
+        self.nodes.len()
+    }
 
-        paths
+    // Iterate over the ids of the nodes with an edge from `id`.
+    pub(crate) fn neighbor_ids(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.adjacency_list[id as usize]
+            .iter()
+            .map(|(neighbor, _)| *neighbor)
     }
 
-    // Helper function for find_all_paths. This function is recursive.
-    fn find_all_paths_helper(
-        &self,
-        start: T,
-        end: T,
-        visited: &mut HashSet<T>,
-        path: &mut Vec<T>,
-        paths: &mut Vec<Vec<T>>,
-    ) {
-        if start == end {
-            paths.push(path.clone());
+    // Iterate over the ids of the nodes with an edge to `id`.
+    pub(crate) fn predecessor_ids(&self, id: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        if !self.is_directed {
+            return Box::new(self.neighbor_ids(id));
+        }
+        match &self.reverse_adjacency {
+            Some(reverse_adjacency) => Box::new(reverse_adjacency[id as usize].iter().copied()),
+            None => {
+                Box::new((0..self.id_bound() as NodeId).filter(move |predecessor| {
+                    self.edge_positions.contains_key(&(*predecessor, id))
+                }))
+            }
+        }
+    }
+
+    // Count the stored edge directions with a negative weight, if the graph keeps count.
+    pub(crate) fn negative_edges(&self) -> Option<usize> {
+        self.is_negative.map(|_| self.negative_edges)
+    }
+
+    // Start counting the edges with a negative weight, beginning with those already stored.
+    fn count_negative_edges(&mut self)
+    where
+        E: Weight,
+    {
+        if self.is_negative.is_some() {
             return;
         }
+        let is_negative: fn(&E) -> bool = |weight| *weight < E::zero();
+        self.negative_edges = self
+            .adjacency_list
+            .iter()
+            .flatten()
+            .filter(|(_, weight)| is_negative(weight))
+            .count();
+        self.is_negative = Some(is_negative);
+    }
 
-        if let Some(neighbors) = self.adjacency_list.get(&start) {
-            for neighbor in neighbors {
-                if !visited.contains(neighbor) {
-                    visited.insert(neighbor.clone());
-                    path.push(neighbor.clone());
-                    self.find_all_paths_helper(neighbor.clone(), end.clone(), visited, path, paths);
-                    path.pop();
-                    visited.remove(neighbor);
+    // Get the id of a node, adding the node first if it is not present yet.
+    fn intern(&mut self, node: T) -> NodeId {
+        if let Some(id) = self.id(&node) {
+            return id;
+        }
+        let id = match self.free_ids.pop() {
+            Some(id) => {
+                self.nodes[id as usize] = Some(node.clone());
+                id
+            }
+            None => {
+                let id = NodeId::try_from(self.nodes.len()).expect("too many nodes for u32 ids");
+                self.nodes.push(Some(node.clone()));
+                self.adjacency_list.push(Vec::new());
+                self.node_data.push(None);
+                if let Some(reverse_adjacency) = &mut self.reverse_adjacency {
+                    reverse_adjacency.push(Vec::new());
                 }
+                id
             }
-        }
+        };
+        self.ids.insert(node, id);
+        id
     }
 
-    // Use depth-first search to find all paths between two nodes with max steps limit.
-    pub fn find_paths_with_max_steps(&self, start: T, end: T, max_steps: usize) -> Vec<Vec<T>> {
-        let mut paths = Vec::new();
-        let mut visited = HashSet::new();
-        let mut path = Vec::new();
-
-        path.push(start.clone());
-        visited.insert(start.clone());
-
-        self.find_paths_with_max_steps_helper(
-            start,
-            end.clone(),
-            max_steps,
-            &mut visited,
-            &mut path,
-            &mut paths,
-        );
+    // Get the policy for edges from a node to itself.
+    pub fn self_loops(&self) -> SelfLoops {
+        self.self_loops
+    }
 
-        paths
+    // Set the policy for edges from a node to itself. It applies to edges added from now on;
+    // self-loops already in the graph are kept, and can be taken out with remove_edge.
+    pub fn set_self_loops(&mut self, policy: SelfLoops) {
+        self.self_loops = policy;
     }
 
-    // Helper function for find_paths_with_max_steps. This function is recursive.
-    fn find_paths_with_max_steps_helper(
-        &self,
-        start: T,
-        end: T,
-        max_steps: usize,
-        visited: &mut HashSet<T>,
-        path: &mut Vec<T>,
-        paths: &mut Vec<Vec<T>>,
-    ) {
-        if start == end {
-            paths.push(path.clone());
+    // Maintain an index of incoming edges, so predecessors and in_degree of a directed graph
+    // take time proportional to the degree instead of a scan over every edge.
+    // Undirected graphs do not need one, since every edge is already stored both ways.
+    pub fn enable_reverse_index(&mut self) {
+        if !self.is_directed || self.reverse_adjacency.is_some() {
             return;
         }
+        let mut reverse_adjacency = vec![Vec::new(); self.id_bound()];
+        for (id, neighbors) in self.adjacency_list.iter().enumerate() {
+            for (neighbor, _) in neighbors {
+                reverse_adjacency[*neighbor as usize].push(id as NodeId);
+            }
+        }
+        self.reverse_adjacency = Some(reverse_adjacency);
+    }
 
-        if path.len() >= max_steps {
-            return;
+    // Check whether incoming edges are indexed, either by the reverse index or because the
+    // graph is undirected.
+    pub fn has_reverse_index(&self) -> bool {
+        !self.is_directed || self.reverse_adjacency.is_some()
+    }
+
+    // Add a node to the graph. Returns true if the node was not already present.
+    pub fn add_node(&mut self, node: T) -> bool {
+        if self.contains_node(&node) {
+            return false;
+        }
+        self.intern(node);
+        true
+    }
+
+    // Add an edge with a weight to the graph, replacing the weight of an existing edge.
+    // Both nodes are added if they are not present yet.
+    // Panics if the graph rejects self-loops and both nodes are the same.
+    pub fn add_weighted_edge(&mut self, vector_x: T, vector_y: T, weight: E)
+    where
+        E: Weight,
+    {
+        self.count_negative_edges();
+        self.add_edge_with(vector_x, vector_y, weight);
+    }
+
+    // Add an edge carrying `data` to the graph, replacing the data of an existing edge.
+    // Both nodes are added if they are not present yet.
+    // Panics if the graph rejects self-loops and both nodes are the same.
+    pub fn add_edge_with(&mut self, vector_x: T, vector_y: T, data: E)
+    where
+        E: Clone,
+    {
+        if self.try_add_edge_with(vector_x, vector_y, data).is_err() {
+            panic!("self-loops are rejected by this graph");
         }
+    }
 
-        if let Some(neighbors) = self.adjacency_list.get(&start) {
-            for neighbor in neighbors {
-                if !visited.contains(neighbor) {
-                    visited.insert(neighbor.clone());
-                    path.push(neighbor.clone());
-                    self.find_paths_with_max_steps_helper(
-                        neighbor.clone(),
-                        end.clone(),
-                        max_steps,
-                        visited,
-                        path,
-                        paths,
-                    );
-                    path.pop();
-                    visited.remove(neighbor);
+    // Add an edge carrying `data` to the graph, or return an error if the graph rejects it as
+    // a self-loop. An ignored self-loop still adds its node.
+    pub fn try_add_edge_with(
+        &mut self,
+        vector_x: T,
+        vector_y: T,
+        data: E,
+    ) -> Result<(), GraphError<T>>
+    where
+        E: Clone,
+    {
+        if vector_x == vector_y {
+            match self.self_loops {
+                SelfLoops::Allow => {}
+                SelfLoops::Reject => return Err(GraphError::SelfLoop(vector_x)),
+                SelfLoops::Ignore => {
+                    self.add_node(vector_x);
+                    return Ok(());
                 }
             }
         }
+
+        let id_x = self.intern(vector_x);
+        let id_y = self.intern(vector_y);
+        if !self.is_directed && id_x != id_y {
+            self.insert_edge(id_y, id_x, data.clone());
+        }
+        self.insert_edge(id_x, id_y, data);
+        Ok(())
+    }
+
+    // Store one direction of an edge, replacing the data of an existing one.
+    fn insert_edge(&mut self, id_x: NodeId, id_y: NodeId, data: E) {
+        let is_negative = self.is_negative;
+        let negative =
+            |data: &E| usize::from(is_negative.is_some_and(|is_negative| is_negative(data)));
+        self.negative_edges += negative(&data);
+        let neighbors = &mut self.adjacency_list[id_x as usize];
+        match self.edge_positions.entry((id_x, id_y)) {
+            Entry::Occupied(position) => {
+                let replaced = std::mem::replace(&mut neighbors[*position.get()].1, data);
+                self.negative_edges -= negative(&replaced);
+            }
+            Entry::Vacant(position) => {
+                position.insert(neighbors.len());
+                neighbors.push((id_y, data));
+                if let Some(reverse_adjacency) = &mut self.reverse_adjacency {
+                    reverse_adjacency[id_y as usize].push(id_x);
+                }
+            }
+        }
+    }
+
+    // Remove one direction of an edge. Returns its data, if the edge existed.
+    fn remove_edge_ids(&mut self, id_x: NodeId, id_y: NodeId) -> Option<E> {
+        let position = self.edge_positions.remove(&(id_x, id_y))?;
+        let neighbors = &mut self.adjacency_list[id_x as usize];
+        let (_, data) = neighbors.swap_remove(position);
+        if self
+            .is_negative
+            .is_some_and(|is_negative| is_negative(&data))
+        {
+            self.negative_edges -= 1;
+        }
+        if let Some((moved, _)) = neighbors.get(position) {
+            self.edge_positions.insert((id_x, *moved), position);
+        }
+        if let Some(reverse_adjacency) = &mut self.reverse_adjacency {
+            let predecessors = &mut reverse_adjacency[id_y as usize];
+            if let Some(index) = predecessors.iter().position(|id| *id == id_x) {
+                predecessors.swap_remove(index);
+            }
+        }
+        Some(data)
+    }
+
+    // Get the data of the edge between two nodes.
+    pub fn edge(&self, vector_x: &T, vector_y: &T) -> Option<&E> {
+        self.edge_at(self.id(vector_x)?, self.id(vector_y)?)
+    }
+
+    // Get the data of the edge between the nodes with two ids.
+    pub(crate) fn edge_at(&self, id_x: NodeId, id_y: NodeId) -> Option<&E> {
+        let position = self.edge_positions.get(&(id_x, id_y))?;
+        Some(&self.adjacency_list[id_x as usize][*position].1)
+    }
+
+    // Get mutable access to the data of one direction of an edge. The count of negative
+    // weights is not updated, so this is only for data that is not a weight.
+    pub(crate) fn edge_mut(&mut self, vector_x: &T, vector_y: &T) -> Option<&mut E> {
+        let id_x = self.id(vector_x)?;
+        let position = self.edge_positions.get(&(id_x, self.id(vector_y)?))?;
+        Some(&mut self.adjacency_list[id_x as usize][*position].1)
+    }
+
+    // Iterate over every edge with its data. An undirected edge is yielded once.
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T, &E)> {
+        self.adjacency_list
+            .iter()
+            .enumerate()
+            .flat_map(|(id, neighbors)| {
+                neighbors
+                    .iter()
+                    .map(move |(neighbor, data)| (id as NodeId, *neighbor, data))
+            })
+            // Both directions of an undirected edge are stored, so keep the one going up.
+            .filter(|(id, neighbor, _)| self.is_directed || id <= neighbor)
+            .map(|(id, neighbor, data)| (self.node_at(id), self.node_at(neighbor), data))
+    }
+
+    // Attach data to a node, adding the node if it is not present yet.
+    // Returns the data the node had before.
+    pub fn set_node_data(&mut self, node: T, data: N) -> Option<N> {
+        let id = self.intern(node);
+        self.node_data[id as usize].replace(data)
+    }
+
+    // Get the data attached to a node.
+    pub fn node_data(&self, node: &T) -> Option<&N> {
+        self.node_data_at(self.id(node)?)
+    }
+
+    // Get mutable access to the data attached to a node.
+    pub fn node_data_mut(&mut self, node: &T) -> Option<&mut N> {
+        let id = self.id(node)?;
+        self.node_data[id as usize].as_mut()
+    }
+
+    // Check whether a node is in the graph.
+    pub fn contains_node(&self, node: &T) -> bool {
+        self.ids.contains_key(node)
+    }
+
+    // Iterate over every node, including sinks and isolated nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter().flatten()
+    }
+
+    // Iterate over the nodes with an edge from `node`.
+    pub fn neighbors<'a>(&'a self, node: &T) -> impl Iterator<Item = &'a T> + 'a {
+        self.id(node)
+            .into_iter()
+            .flat_map(|id| self.neighbor_ids(id))
+            .map(|neighbor| self.node_at(neighbor))
+    }
+
+    // Count the nodes, including sinks and isolated nodes.
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    // Count the edges. An undirected edge is counted once, and so is a self-loop.
+    pub fn edge_count(&self) -> usize {
+        let degrees = self.edge_positions.len();
+        if self.is_directed {
+            return degrees;
+        }
+        let self_loops = (0..self.id_bound() as NodeId)
+            .filter(|id| self.edge_positions.contains_key(&(*id, *id)))
+            .count();
+        (degrees + self_loops) / 2
+    }
+
+    // Remove the edge between two nodes. Returns true if the edge existed.
+    pub fn remove_edge(&mut self, vector_x: &T, vector_y: &T) -> bool {
+        let (Some(id_x), Some(id_y)) = (self.id(vector_x), self.id(vector_y)) else {
+            return false;
+        };
+        let removed = self.remove_edge_ids(id_x, id_y).is_some();
+        if removed && !self.is_directed {
+            self.remove_edge_ids(id_y, id_x);
+        }
+        removed
+    }
+
+    // Remove a node together with every edge from or to it. Returns true if the node existed.
+    pub fn remove_node(&mut self, node: &T) -> bool {
+        let Some(id) = self.ids.remove(node) else {
+            return false;
+        };
+
+        let incoming: Vec<NodeId> = self.predecessor_ids(id).collect();
+        for predecessor in incoming {
+            self.remove_edge_ids(predecessor, id);
+        }
+        let outgoing: Vec<NodeId> = self.neighbor_ids(id).collect();
+        for neighbor in outgoing {
+            self.remove_edge_ids(id, neighbor);
+        }
+
+        self.nodes[id as usize] = None;
+        self.node_data[id as usize] = None;
+        self.free_ids.push(id);
+        true
+    }
+
+    // Iterate over the nodes with an edge to `node`.
+    // Without a reverse index, a directed graph has to scan every node.
+    pub fn predecessors<'a>(&'a self, node: &T) -> Box<dyn Iterator<Item = &'a T> + 'a> {
+        match self.id(node) {
+            Some(id) => Box::new(
+                self.predecessor_ids(id)
+                    .map(|predecessor| self.node_at(predecessor)),
+            ),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    // Check whether a node has an edge to itself.
+    pub fn has_self_loop(&self, node: &T) -> bool {
+        self.edge(node, node).is_some()
+    }
+
+    // Count the edges into `node`. In an undirected graph this is the degree.
+    pub fn in_degree(&self, node: &T) -> usize {
+        if !self.is_directed {
+            return self.degree(node);
+        }
+        self.predecessors(node).count()
+    }
+
+    // Count the edges out of `node`. In an undirected graph this is the degree.
+    pub fn out_degree(&self, node: &T) -> usize {
+        if !self.is_directed {
+            return self.degree(node);
+        }
+        self.id(node)
+            .map_or(0, |id| self.adjacency_list[id as usize].len())
+    }
+
+    // Count the edge ends at `node`. A self-loop has both of its ends there, so it counts twice.
+    pub fn degree(&self, node: &T) -> usize {
+        if self.is_directed {
+            return self.in_degree(node) + self.out_degree(node);
+        }
+        let neighbors = self
+            .id(node)
+            .map_or(0, |id| self.adjacency_list[id as usize].len());
+        neighbors + usize::from(self.has_self_loop(node))
+    }
+
+    // Lazily iterate over all paths between two nodes.
+    pub fn paths_iter(&self, start: T, end: T) -> PathsIter<'_, T, E, N> {
+        self.view().paths_iter(start, end)
+    }
+
+    // Use depth-first search to find all paths between two nodes
+    pub fn find_all_paths(&self, start: T, end: T) -> Vec<Vec<T>> {
+        self.paths_iter(start, end).collect()
+    }
+
+    // Use depth-first search to find all paths between two nodes with max steps limit.
+    // The limit is counted in nodes, so a path of `max_steps` nodes is still accepted.
+    pub fn find_paths_with_max_steps(&self, start: T, end: T, max_steps: usize) -> Vec<Vec<T>> {
+        self.view()
+            .paths_iter_with_max_steps(start, end, max_steps)
+            .collect()
+    }
+
+    // Use depth-first search to find all paths between two nodes, each with the data of the
+    // edges it traverses.
+    pub fn find_all_paths_with_edges(&self, start: T, end: T) -> Vec<(Vec<T>, Vec<&E>)> {
+        self.view().find_all_paths_with_edges(start, end)
+    }
+}
+
+impl<T, E, N> Graph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+    E: Weight,
+{
+    // Get the weight of the edge between two nodes.
+    pub fn weight(&self, vector_x: &T, vector_y: &T) -> Option<E> {
+        self.view().weight(vector_x, vector_y)
+    }
+
+    // Sum the weights along a path. Returns None if two consecutive nodes are not connected.
+    pub fn path_cost(&self, path: &[T]) -> Option<E> {
+        self.view().path_cost(path)
+    }
+
+    // Use depth-first search to find all paths between two nodes, each with its total cost.
+    pub fn find_all_weighted_paths(&self, start: T, end: T) -> Vec<(Vec<T>, E)> {
+        self.view().find_all_weighted_paths(start, end)
+    }
+
+    // Use depth-first search to find all paths between two nodes whose total cost is at most
+    // `max_cost`, each with its total cost.
+    pub fn find_weighted_paths_with_max_cost(
+        &self,
+        start: T,
+        end: T,
+        max_cost: E,
+    ) -> Vec<(Vec<T>, E)> {
+        self.view()
+            .find_weighted_paths_with_max_cost(start, end, max_cost)
     }
 }
 
@@ -146,13 +610,43 @@ mod tests {
     fn test_add_edge() {
         let mut graph = Graph::new(Some(false));
         graph.add_edge(1, 2);
-        assert!(graph.adjacency_list.get(&1).unwrap().contains(&2));
-        assert!(graph.adjacency_list.get(&2).is_some());
+        assert!(graph.edge(&1, &2).is_some());
+        assert!(graph.contains_node(&2));
+
+        let mut graph = Graph::new(Some(true));
+        graph.add_edge(1, 2);
+        assert!(graph.edge(&1, &2).is_some());
+        assert!(graph.neighbors(&2).next().is_none());
+    }
 
+    #[test]
+    fn test_nodes_and_counts() {
         let mut graph = Graph::new(Some(true));
         graph.add_edge(1, 2);
-        assert!(graph.adjacency_list.get(&1).unwrap().contains(&2));
-        assert!(!graph.adjacency_list.get(&2).is_some());
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 3);
+        assert!(graph.add_node(4));
+        assert!(!graph.add_node(2));
+        assert!(graph.contains_node(&3));
+        assert!(graph.contains_node(&4));
+        assert!(!graph.contains_node(&5));
+        let mut nodes: Vec<_> = graph.nodes().copied().collect();
+        nodes.sort();
+        assert_eq!(nodes, vec![1, 2, 3, 4]);
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 3);
+
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 3);
+        graph.add_node(4);
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 3);
+        graph.remove_node(&2);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 1);
     }
 
     #[test]
@@ -177,30 +671,325 @@ mod tests {
         assert_eq!(paths, vec![vec![1, 2, 3, 4]]);
     }
 
+    #[test]
+    fn test_remove_edge() {
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        assert!(graph.remove_edge(&2, &1));
+        assert!(graph.edge(&1, &2).is_none());
+        assert!(graph.edge(&2, &1).is_none());
+        assert!(!graph.remove_edge(&1, &2));
+        assert!(!graph.remove_edge(&1, &4));
+        assert_eq!(graph.find_all_paths(1, 3), Vec::<Vec<i32>>::new());
+
+        let mut graph = Graph::new(Some(true));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        assert!(!graph.remove_edge(&2, &3));
+        assert!(graph.remove_edge(&1, &2));
+        assert!(graph.edge(&1, &2).is_none());
+        assert!(graph.edge(&2, &1).is_some());
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 1);
+        assert!(graph.remove_node(&2));
+        assert!(!graph.contains_node(&2));
+        assert!(graph.nodes().all(|node| graph.edge(node, &2).is_none()));
+        assert_eq!(graph.find_all_paths(1, 3), vec![vec![1, 3]]);
+        assert!(!graph.remove_node(&2));
+
+        let mut graph = Graph::new(Some(true));
+        graph.add_edge(1, 2);
+        graph.add_edge(3, 2);
+        graph.add_edge(2, 4);
+        assert!(graph.remove_node(&4));
+        assert!(graph.neighbors(&2).next().is_none());
+        assert!(graph.remove_node(&2));
+        assert!(graph.neighbors(&1).next().is_none());
+        assert!(graph.neighbors(&3).next().is_none());
+        assert!(!graph.remove_node(&5));
+    }
+
+    #[test]
+    fn test_node_ids() {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_weighted_edge("a", "b", 1);
+        graph.add_weighted_edge("a", "c", 2);
+        graph.add_weighted_edge("a", "d", 3);
+        // Removing the first edge moves the last one into its slot.
+        assert!(graph.remove_edge(&"a", &"b"));
+        graph.add_weighted_edge("a", "d", 4);
+        assert_eq!(graph.edge(&"a", &"d"), Some(&4));
+        assert_eq!(graph.edge(&"a", &"c"), Some(&2));
+        assert_eq!(graph.edge_count(), 2);
+
+        // The id of a removed node is reused without leaking its edges or data.
+        assert!(graph.remove_node(&"c"));
+        graph.add_weighted_edge("e", "a", 5);
+        assert_eq!(graph.id_bound(), 4);
+        assert_eq!(graph.neighbors(&"e").collect::<Vec<_>>(), vec![&"a"]);
+        assert_eq!(graph.find_all_paths("e", "d"), vec![vec!["e", "a", "d"]]);
+
+        let mut other = Graph::new_weighted(Some(true));
+        other.add_weighted_edge("e", "a", 5);
+        other.add_weighted_edge("a", "d", 4);
+        other.add_node("b");
+        assert!(graph == other);
+        other.add_weighted_edge("a", "d", 3);
+        assert!(graph != other);
+    }
+
+    #[test]
+    fn test_reverse_index() {
+        let mut graph = Graph::new(Some(true));
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        assert!(!graph.has_reverse_index());
+        assert_eq!(graph.in_degree(&3), 2);
+
+        graph.enable_reverse_index();
+        assert!(graph.has_reverse_index());
+        graph.add_edge(3, 4);
+        graph.add_edge(4, 3);
+        graph.add_node(5);
+        let mut predecessors: Vec<_> = graph.predecessors(&3).copied().collect();
+        predecessors.sort();
+        assert_eq!(predecessors, vec![1, 2, 4]);
+        assert_eq!(graph.in_degree(&4), 1);
+        assert_eq!(graph.in_degree(&1), 0);
+        assert_eq!(graph.in_degree(&5), 0);
+
+        graph.remove_edge(&2, &3);
+        assert_eq!(graph.in_degree(&3), 2);
+        graph.remove_node(&4);
+        assert_eq!(graph.predecessors(&3).collect::<Vec<_>>(), vec![&1]);
+        assert!(graph.neighbors(&3).next().is_none());
+
+        let mut scanned = graph.clone();
+        scanned.reverse_adjacency = None;
+        assert!(graph == scanned);
+        assert_eq!(scanned.predecessors(&3).collect::<Vec<_>>(), vec![&1]);
+
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        assert!(graph.has_reverse_index());
+        assert_eq!(graph.in_degree(&2), 2);
+    }
+
+    #[test]
+    fn test_weighted_paths() {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_weighted_edge("A", "B", 4u32);
+        graph.add_weighted_edge("B", "D", 1);
+        graph.add_weighted_edge("A", "C", 1);
+        graph.add_weighted_edge("C", "D", 2);
+        graph.add_weighted_edge("C", "B", 1);
+        assert_eq!(graph.weight(&"A", &"B"), Some(4));
+        assert_eq!(graph.weight(&"B", &"A"), None);
+        assert_eq!(graph.path_cost(&["A", "C", "B", "D"]), Some(3));
+        assert_eq!(graph.path_cost(&["A", "D"]), None);
+
+        let mut paths = graph.find_all_weighted_paths("A", "D");
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                (vec!["A", "B", "D"], 5),
+                (vec!["A", "C", "B", "D"], 3),
+                (vec!["A", "C", "D"], 3),
+            ]
+        );
+        let mut paths = graph.find_weighted_paths_with_max_cost("A", "D", 3);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![(vec!["A", "C", "B", "D"], 3), (vec!["A", "C", "D"], 3)]
+        );
+
+        // Replacing an edge keeps a single edge with the new weight.
+        graph.add_weighted_edge("A", "B", 1);
+        assert_eq!(graph.edge_count(), 5);
+        assert_eq!(
+            graph.find_weighted_paths_with_max_cost("A", "D", 2),
+            vec![(vec!["A", "B", "D"], 2)]
+        );
+
+        let mut graph = Graph::new_weighted(None);
+        graph.add_weighted_edge(1, 2, 0.5);
+        graph.add_weighted_edge(2, 3, 0.25);
+        assert_eq!(graph.weight(&2, &1), Some(0.5));
+        assert_eq!(
+            graph.find_all_weighted_paths(3, 1),
+            vec![(vec![3, 2, 1], 0.75)]
+        );
+    }
+
+    #[test]
+    fn test_negative_edge_count() {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_weighted_edge(1, 2, -1);
+        graph.add_weighted_edge(2, 3, -2);
+        graph.add_weighted_edge(3, 4, 1);
+        assert_eq!(graph.negative_edges(), Some(2));
+        graph.add_weighted_edge(1, 2, 1);
+        assert_eq!(graph.negative_edges(), Some(1));
+        graph.add_weighted_edge(3, 4, -1);
+        graph.remove_edge(&2, &3);
+        assert_eq!(graph.negative_edges(), Some(1));
+        assert_eq!(graph.clone().freeze().negative_edges, Some(1));
+        graph.remove_node(&4);
+        assert_eq!(graph.negative_edges(), Some(0));
+
+        // An undirected edge is stored both ways. Edges added before the first weighted one
+        // are counted when it arrives.
+        let mut graph = Graph::new_weighted(Some(false));
+        graph.add_edge_with(1, 2, -1);
+        assert_eq!(graph.negative_edges(), None);
+        assert_eq!(graph.clone().freeze().negative_edges, None);
+        graph.add_weighted_edge(2, 3, -2);
+        assert_eq!(graph.negative_edges(), Some(4));
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Relation {
+        Calls,
+        Imports,
+    }
+
+    #[test]
+    fn test_edge_data() {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_edge_with("main", "parse", Relation::Calls);
+        graph.add_edge_with("main", "config", Relation::Imports);
+        graph.add_edge_with("parse", "config", Relation::Imports);
+        assert_eq!(graph.edge(&"main", &"parse"), Some(&Relation::Calls));
+        assert_eq!(graph.edge(&"parse", &"main"), None);
+
+        let mut edges: Vec<_> = graph.edges().map(|(x, y, _)| (*x, *y)).collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![("main", "config"), ("main", "parse"), ("parse", "config")]
+        );
+
+        let mut paths = graph.find_all_paths_with_edges("main", "config");
+        paths.sort_by_key(|(path, _)| path.len());
+        assert_eq!(paths[0], (vec!["main", "config"], vec![&Relation::Imports]));
+        assert_eq!(
+            paths[1],
+            (
+                vec!["main", "parse", "config"],
+                vec![&Relation::Calls, &Relation::Imports]
+            )
+        );
+
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 3);
+        assert_eq!(graph.edges().count(), graph.edge_count());
+        assert_eq!(graph.edge(&2, &1), Some(&()));
+    }
+
+    #[test]
+    fn test_self_loops() {
+        let mut graph = Graph::new(Some(false));
+        assert_eq!(graph.self_loops(), SelfLoops::Allow);
+        graph.add_edge(1, 1);
+        graph.add_edge(1, 2);
+        assert!(graph.has_self_loop(&1));
+        assert_eq!(graph.degree(&1), 3);
+        assert_eq!(graph.in_degree(&1), 3);
+        assert_eq!(graph.degree(&2), 1);
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(graph.edges().count(), 2);
+        assert_eq!(graph.find_all_paths(1, 2), vec![vec![1, 2]]);
+        assert_eq!(graph.find_all_paths(1, 1), vec![vec![1]]);
+
+        // The policy only governs new edges; the existing self-loop stays.
+        graph.set_self_loops(SelfLoops::Reject);
+        assert!(graph.has_self_loop(&1));
+        assert_eq!(graph.try_add_edge(2, 2), Err(GraphError::SelfLoop(2)));
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(graph.try_add_edge(2, 3), Ok(()));
+        assert!(graph.remove_edge(&1, &1));
+        assert_eq!(graph.degree(&1), 1);
+
+        graph.set_self_loops(SelfLoops::Ignore);
+        graph.add_edge(4, 4);
+        assert!(graph.contains_node(&4));
+        assert_eq!(graph.degree(&4), 0);
+
+        let mut graph = Graph::new(Some(true));
+        graph.add_edge(1, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(3, 1);
+        assert_eq!(graph.out_degree(&1), 2);
+        assert_eq!(graph.in_degree(&1), 2);
+        assert_eq!(graph.degree(&1), 4);
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "self-loops are rejected")]
+    fn test_add_edge_rejected_self_loop() {
+        let mut graph = Graph::new(Some(true));
+        graph.set_self_loops(SelfLoops::Reject);
+        graph.add_edge("a", "a");
+    }
+
+    #[test]
+    fn test_node_data() {
+        let mut graph: Graph<&str, (), u32> = Graph::new_with_data(Some(true));
+        graph.add_edge("api", "db");
+        assert_eq!(graph.node_data(&"api"), None);
+        assert_eq!(graph.set_node_data("api", 1), None);
+        assert_eq!(graph.set_node_data("api", 2), Some(1));
+        assert_eq!(graph.node_data(&"api"), Some(&2));
+
+        // Setting data on an unknown node adds it.
+        graph.set_node_data("cache", 3);
+        assert!(graph.contains_node(&"cache"));
+        assert_eq!(graph.node_count(), 3);
+
+        *graph.node_data_mut(&"cache").unwrap() += 1;
+        assert_eq!(graph.node_data(&"cache"), Some(&4));
+        assert_eq!(graph.node_data_mut(&"db"), None);
+
+        let mut other = graph.clone();
+        assert!(graph == other);
+        other.set_node_data("db", 0);
+        assert!(graph != other);
+
+        graph.remove_node(&"cache");
+        assert_eq!(graph.node_data(&"cache"), None);
+    }
+
     // Test the Graph struct with strings.
     #[test]
     fn test_add_edge_string() {
         let mut graph = Graph::new(Some(false));
         graph.add_edge("Node1".to_string(), "Node2".to_string());
         assert!(graph
-            .adjacency_list
-            .get(&"Node1".to_string())
-            .unwrap()
-            .contains(&"Node2".to_string()));
+            .neighbors(&"Node1".to_string())
+            .eq([&"Node2".to_string()]));
         assert!(graph
-            .adjacency_list
-            .get(&"Node2".to_string())
-            .unwrap()
-            .contains(&"Node1".to_string()));
+            .neighbors(&"Node2".to_string())
+            .eq([&"Node1".to_string()]));
 
         let mut graph = Graph::new(Some(true));
         graph.add_edge("Node1".to_string(), "Node2".to_string());
         assert!(graph
-            .adjacency_list
-            .get(&"Node1".to_string())
-            .unwrap()
-            .contains(&"Node2".to_string()));
-        assert!(!graph.adjacency_list.get(&"Node2".to_string()).is_some());
+            .neighbors(&"Node1".to_string())
+            .eq([&"Node2".to_string()]));
+        assert!(graph.neighbors(&"Node2".to_string()).next().is_none());
     }
 
     #[test]
@@ -1 +1,16 @@
+mod bitset;
+pub mod error;
+#[cfg(test)]
+mod fixtures;
+pub mod frozen;
 pub mod graph;
+pub mod multigraph;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod paths;
+pub mod query;
+pub mod search;
+pub mod shortest_paths;
+pub mod traversal;
+mod view;
+pub mod weight;
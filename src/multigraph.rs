@@ -0,0 +1,362 @@
+use std::fmt;
+use std::hash::Hash;
+
+use crate::graph::Graph;
+use crate::paths::{Constraints, PathLength, PathSearch};
+
+// Identity of an edge in a MultiGraph. Ids are never reused, even after their edge is removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeId(pub(crate) usize);
+
+impl EdgeId {
+    // Position of the edge in the order edges were added.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+// An edge of a MultiGraph with its endpoints, in the order they were given to add_edge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiEdge<T, E = ()> {
+    pub source: T,
+    pub target: T,
+    pub data: E,
+}
+
+// A graph that keeps every edge added between two nodes, so parallel edges stay distinct and
+// are told apart by their EdgeId. An undirected edge is stored once and listed at both ends.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MultiGraph<T: Eq + Hash + Clone, E = ()> {
+    // Indexed by EdgeId. Removed edges leave a hole so the other ids stay valid.
+    pub(crate) edges: Vec<Option<MultiEdge<T, E>>>,
+    // The nodes, interned like in any Graph, linked wherever at least one edge runs. Each link
+    // carries the ids of its edges in the order they were added, so path searches run on the
+    // links and expand every path of nodes into its sequences of edges.
+    pub(crate) links: Graph<T, Vec<EdgeId>>,
+}
+
+impl<T, E> fmt::Debug for MultiGraph<T, E>
+where
+    T: Eq + Hash + Clone + fmt::Debug,
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiGraph")
+            .field("is_directed", &self.links.is_directed)
+            .field("nodes", &self.links.nodes().collect::<Vec<_>>())
+            .field("edges", &self.edges)
+            .finish()
+    }
+}
+
+impl<T> MultiGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub fn new(is_directed: Option<bool>) -> Self {
+        Self::new_weighted(is_directed)
+    }
+
+    // Add an edge to the graph, even if the two nodes are already connected.
+    // Both nodes are added if they are not present yet.
+    pub fn add_edge(&mut self, vector_x: T, vector_y: T) -> EdgeId {
+        self.add_edge_with(vector_x, vector_y, ())
+    }
+}
+
+impl<T, E> MultiGraph<T, E>
+where
+    T: Eq + Hash + Clone,
+{
+    // Create a multigraph whose edges carry data, such as weights or labels.
+    pub fn new_weighted(is_directed: Option<bool>) -> Self {
+        MultiGraph {
+            edges: Vec::new(),
+            links: Graph::new_weighted(is_directed),
+        }
+    }
+
+    // Add a node to the graph. Returns true if the node was not already present.
+    pub fn add_node(&mut self, node: T) -> bool {
+        self.links.add_node(node)
+    }
+
+    // Add an edge carrying `data` to the graph, even if the two nodes are already connected.
+    // Both nodes are added if they are not present yet.
+    pub fn add_edge_with(&mut self, vector_x: T, vector_y: T, data: E) -> EdgeId {
+        let id = EdgeId(self.edges.len());
+        match self.links.edge_mut(&vector_x, &vector_y) {
+            Some(parallel) => {
+                parallel.push(id);
+                // The link of an undirected edge is stored both ways.
+                if !self.links.is_directed && vector_x != vector_y {
+                    if let Some(parallel) = self.links.edge_mut(&vector_y, &vector_x) {
+                        parallel.push(id);
+                    }
+                }
+            }
+            None => {
+                self.links
+                    .add_edge_with(vector_x.clone(), vector_y.clone(), vec![id]);
+            }
+        }
+        self.edges.push(Some(MultiEdge {
+            source: vector_x,
+            target: vector_y,
+            data,
+        }));
+        id
+    }
+
+    // Get an edge by its id.
+    pub fn edge(&self, id: EdgeId) -> Option<&MultiEdge<T, E>> {
+        self.edges.get(id.0)?.as_ref()
+    }
+
+    // Iterate over every edge with its id, in the order they were added.
+    pub fn edges(&self) -> impl Iterator<Item = (EdgeId, &MultiEdge<T, E>)> {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter_map(|(index, edge)| Some((EdgeId(index), edge.as_ref()?)))
+    }
+
+    // Iterate over the ids of the edges from `vector_x` to `vector_y`.
+    // In an undirected graph this includes the edges added the other way round.
+    pub fn edges_between<'a>(
+        &'a self,
+        vector_x: &'a T,
+        vector_y: &'a T,
+    ) -> impl Iterator<Item = EdgeId> + 'a {
+        self.links
+            .edge(vector_x, vector_y)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    // Remove an edge by its id. Returns the removed edge, if it existed.
+    pub fn remove_edge(&mut self, id: EdgeId) -> Option<MultiEdge<T, E>> {
+        let edge = self.edges.get_mut(id.0)?.take()?;
+        let (source, target) = (&edge.source, &edge.target);
+        for (from, to) in [(source, target), (target, source)] {
+            if let Some(parallel) = self.links.edge_mut(from, to) {
+                parallel.retain(|other| *other != id);
+            }
+        }
+        if self
+            .links
+            .edge(source, target)
+            .is_some_and(|parallel| parallel.is_empty())
+        {
+            self.links.remove_edge(source, target);
+        }
+        Some(edge)
+    }
+
+    // Check whether a node is in the graph.
+    pub fn contains_node(&self, node: &T) -> bool {
+        self.links.contains_node(node)
+    }
+
+    // Iterate over every node, including sinks and isolated nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.links.nodes()
+    }
+
+    // Count the nodes, including sinks and isolated nodes.
+    pub fn node_count(&self) -> usize {
+        self.links.node_count()
+    }
+
+    // Count the edges. Parallel edges are counted separately.
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().flatten().count()
+    }
+
+    // Use depth-first search to lazily iterate over all paths between two nodes, each as the
+    // sequence of edges it traverses. Paths that differ only in which parallel edge they take
+    // are yielded separately.
+    pub fn paths_iter(&self, start: T, end: T) -> MultiPathsIter<'_, T, E> {
+        MultiPathsIter {
+            graph: self,
+            search: PathSearch::new(
+                self.links.view(),
+                start,
+                end,
+                Constraints::new(PathLength::any()),
+            ),
+            choices: Vec::new(),
+            positions: None,
+        }
+    }
+
+    // Use depth-first search to find all paths between two nodes, each as the sequence of edges
+    // it traverses. A start that is also the end gives a single empty path.
+    pub fn find_all_paths(&self, start: T, end: T) -> Vec<Vec<EdgeId>> {
+        self.paths_iter(start, end).collect()
+    }
+
+    // Translate a sequence of edges starting at `start` into the nodes it visits.
+    // Returns None if the edges do not form a walk from `start` in this graph.
+    pub fn path_nodes(&self, start: &T, path: &[EdgeId]) -> Option<Vec<T>> {
+        let mut nodes = vec![start.clone()];
+        for id in path {
+            let node = &nodes[nodes.len() - 1];
+            let edge = self.edge(*id)?;
+            let next = if edge.source == *node {
+                &edge.target
+            } else if !self.links.is_directed && edge.target == *node {
+                &edge.source
+            } else {
+                return None;
+            };
+            nodes.push(next.clone());
+        }
+        Some(nodes)
+    }
+}
+
+// Lazy iterator over the paths between two nodes of a MultiGraph, as edge sequences.
+pub struct MultiPathsIter<'a, T: Eq + Hash + Clone, E = ()> {
+    graph: &'a MultiGraph<T, E>,
+    // Search for the paths of nodes over the links.
+    search: PathSearch<'a, T, Vec<EdgeId>, ()>,
+    // Parallel edges between each pair of consecutive nodes on the last path of nodes.
+    choices: Vec<&'a [EdgeId]>,
+    // Edge to take from each of `choices` for the next path, or None once every combination
+    // has been yielded.
+    positions: Option<Vec<usize>>,
+}
+
+impl<T, E> Iterator for MultiPathsIter<'_, T, E>
+where
+    T: Eq + Hash + Clone,
+{
+    type Item = Vec<EdgeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(positions) = &mut self.positions {
+                let path = positions
+                    .iter()
+                    .zip(&self.choices)
+                    .map(|(position, parallel)| parallel[*position])
+                    .collect();
+                // Count through the combinations, the edge nearest the end changing fastest.
+                let advanced =
+                    positions
+                        .iter_mut()
+                        .zip(&self.choices)
+                        .rev()
+                        .any(|(position, parallel)| {
+                            *position = (*position + 1) % parallel.len();
+                            *position != 0
+                        });
+                if !advanced {
+                    self.positions = None;
+                }
+                return Some(path);
+            }
+
+            let links = &self.graph.links;
+            let nodes = self.search.next_ids()?;
+            self.choices = nodes
+                .windows(2)
+                .map(|pair| {
+                    links
+                        .edge_at(pair[0], pair[1])
+                        .expect("paths only follow links")
+                        .as_slice()
+                })
+                .collect();
+            self.positions = Some(vec![0; self.choices.len()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_edges() {
+        let mut graph = MultiGraph::new_weighted(Some(false));
+        let red_ab = graph.add_edge_with("A", "B", "red");
+        let blue_ab = graph.add_edge_with("B", "A", "blue");
+        let red_bc = graph.add_edge_with("B", "C", "red");
+        let green_ac = graph.add_edge_with("A", "C", "green");
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(
+            graph.edges_between(&"A", &"B").collect::<Vec<_>>(),
+            vec![red_ab, blue_ab]
+        );
+        assert_eq!(graph.edge(blue_ab).unwrap().data, "blue");
+
+        let mut paths = graph.find_all_paths("A", "C");
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![vec![red_ab, red_bc], vec![blue_ab, red_bc], vec![green_ac]]
+        );
+        assert_eq!(
+            graph.path_nodes(&"A", &[blue_ab, red_bc]),
+            Some(vec!["A", "B", "C"])
+        );
+        assert_eq!(graph.path_nodes(&"C", &[blue_ab]), None);
+        assert_eq!(graph.find_all_paths("A", "A"), vec![Vec::new()]);
+
+        assert_eq!(graph.remove_edge(red_ab).unwrap().data, "red");
+        assert_eq!(graph.remove_edge(red_ab), None);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.find_all_paths("C", "A").len(), 2);
+    }
+
+    #[test]
+    fn test_directed_multigraph() {
+        let mut graph = MultiGraph::new(Some(true));
+        let first = graph.add_edge(1, 2);
+        let second = graph.add_edge(1, 2);
+        let back = graph.add_edge(2, 1);
+        let loop_edge = graph.add_edge(2, 2);
+        let onward = graph.add_edge(2, 3);
+        graph.add_node(4);
+        assert_eq!(graph.edges_between(&2, &1).collect::<Vec<_>>(), vec![back]);
+        assert_eq!(graph.edges().count(), 5);
+        assert_eq!(graph.edge(loop_edge).unwrap().target, 2);
+
+        assert_eq!(
+            graph.find_all_paths(1, 3),
+            vec![vec![first, onward], vec![second, onward]]
+        );
+        assert_eq!(graph.find_all_paths(3, 1), Vec::<Vec<EdgeId>>::new());
+        assert_eq!(graph.find_all_paths(4, 1), Vec::<Vec<EdgeId>>::new());
+        assert_eq!(graph.path_nodes(&2, &[first]), None);
+    }
+
+    #[test]
+    fn test_parallel_edge_combinations() {
+        let mut graph = MultiGraph::new(Some(true));
+        let (ab1, ab2) = (graph.add_edge("A", "B"), graph.add_edge("A", "B"));
+        let (bc1, bc2) = (graph.add_edge("B", "C"), graph.add_edge("B", "C"));
+        assert_eq!(
+            graph.find_all_paths("A", "C"),
+            vec![
+                vec![ab1, bc1],
+                vec![ab1, bc2],
+                vec![ab2, bc1],
+                vec![ab2, bc2]
+            ]
+        );
+        assert_eq!(graph.paths_iter("A", "C").nth(3), Some(vec![ab2, bc2]));
+
+        graph.remove_edge(ab1);
+        assert_eq!(graph.find_all_paths("A", "C").len(), 2);
+        graph.remove_edge(ab2);
+        assert!(graph.find_all_paths("A", "C").is_empty());
+        assert!(graph.edges_between(&"A", &"B").next().is_none());
+        assert!(graph.contains_node(&"A"));
+        assert_eq!(graph.find_all_paths("D", "D"), vec![Vec::new()]);
+    }
+}
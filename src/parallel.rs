@@ -0,0 +1,120 @@
+use std::hash::Hash;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use crate::graph::NodeId;
+use crate::paths::{Constraints, Goal, PathLength, PathSearch, Reachability};
+use crate::view::View;
+
+// Number of levels below the start that are expanded before the search is split across tasks.
+const SPLIT_DEPTH: usize = 2;
+
+impl<T, E, N> View<'_, T, E, N>
+where
+    T: Eq + Hash + Clone + Send + Sync,
+    E: Sync,
+    N: Sync,
+{
+    pub(crate) fn par_find_all_paths(self, start: T, end: T) -> Vec<Vec<T>> {
+        let goal = Goal::End(end.clone());
+        let reachability = Arc::new(Reachability::to(self, self.id(&start), &goal, None));
+        let (Some(start), Some(end_id)) = (self.id(&start), self.id(&end)) else {
+            // Only a start that is also the end can form a path with a node outside the graph.
+            return if start == end {
+                vec![vec![start]]
+            } else {
+                Vec::new()
+            };
+        };
+        let (found, prefixes) = self.split_paths(start, end_id, &reachability);
+        let mut paths: Vec<Vec<T>> = found
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|node| self.node_at(*node).clone())
+                    .collect()
+            })
+            .collect();
+
+        let rest: Vec<Vec<T>> = prefixes
+            .into_par_iter()
+            .flat_map_iter(|prefix| {
+                let mut search = PathSearch::with_prefix(
+                    self,
+                    prefix,
+                    end.clone(),
+                    Constraints::new(PathLength::any()),
+                    reachability.clone(),
+                );
+                std::iter::from_fn(move || search.next_owned())
+            })
+            .collect();
+
+        paths.extend(rest);
+        paths
+    }
+
+    // Expand the first levels of the search. Returns the paths that already reach the end,
+    // and the prefixes left for the parallel tasks to finish, both as node ids.
+    fn split_paths(
+        self,
+        start: NodeId,
+        end: NodeId,
+        reachability: &Reachability,
+    ) -> (Vec<Vec<NodeId>>, Vec<Vec<NodeId>>) {
+        let mut paths = Vec::new();
+        if reachability.distance(start).is_none() {
+            return (paths, Vec::new());
+        }
+        let mut prefixes = vec![vec![start]];
+
+        for depth in 0..=SPLIT_DEPTH {
+            let mut next = Vec::new();
+            for prefix in prefixes {
+                let last = prefix[prefix.len() - 1];
+                if last == end {
+                    paths.push(prefix);
+                    continue;
+                }
+                if depth == SPLIT_DEPTH {
+                    next.push(prefix);
+                    continue;
+                }
+                for neighbor in self.neighbors(last) {
+                    if !prefix.contains(&neighbor) && reachability.distance(neighbor).is_some() {
+                        let mut extended = prefix.clone();
+                        extended.push(neighbor);
+                        next.push(extended);
+                    }
+                }
+            }
+            prefixes = next;
+        }
+
+        (paths, prefixes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::random_graph;
+    use crate::search::Search;
+
+    #[test]
+    fn test_par_find_all_paths() {
+        for seed in 0..20 {
+            let graph = random_graph(seed, 10, 25, seed % 2 == 0);
+            for (start, end) in [(0, 9), (1, 5), (3, 3)] {
+                let mut expected = graph.find_all_paths(start, end);
+                let mut paths = graph.par_find_all_paths(start, end);
+                let mut frozen_paths = graph.clone().freeze().par_find_all_paths(start, end);
+                expected.sort();
+                paths.sort();
+                frozen_paths.sort();
+                assert_eq!(paths, expected);
+                assert_eq!(frozen_paths, expected);
+            }
+        }
+    }
+}
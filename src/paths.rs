@@ -0,0 +1,1273 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+use std::vec;
+
+use crate::bitset::BitSet;
+use crate::graph::NodeId;
+use crate::traversal::BreadthFirst;
+use crate::view::{EdgesFrom, View};
+use crate::weight::Weight;
+
+// Bounds on the length of a path, counted either in edges (hops) or in nodes.
+// `PathLength::edges(3..=3)` keeps paths of exactly 3 hops, `PathLength::edges(2..=5)` those
+// between 2 and 5 hops, and `PathLength::nodes(..=4)` those with at most 4 nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PathLength {
+    // Both bounds are inclusive and counted in nodes.
+    pub(crate) min_nodes: usize,
+    pub(crate) max_nodes: Option<usize>,
+}
+
+impl PathLength {
+    // Accept paths of any length.
+    pub fn any() -> Self {
+        PathLength {
+            min_nodes: 0,
+            max_nodes: None,
+        }
+    }
+
+    // Bound the number of edges in a path.
+    pub fn edges<R: RangeBounds<usize>>(range: R) -> Self {
+        Self::from_range(range, 1)
+    }
+
+    // Bound the number of nodes in a path.
+    pub fn nodes<R: RangeBounds<usize>>(range: R) -> Self {
+        Self::from_range(range, 0)
+    }
+
+    // Convert a range counted in edges (`offset` 1) or nodes (`offset` 0) to inclusive node bounds.
+    // A maximum too large to count in nodes is no bound at all.
+    fn from_range<R: RangeBounds<usize>>(range: R, offset: usize) -> Self {
+        let min_nodes = match range.start_bound() {
+            Bound::Included(&min) => min.saturating_add(offset),
+            Bound::Excluded(&min) => min.saturating_add(offset).saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let max_nodes = match range.end_bound() {
+            Bound::Included(&max) => max.checked_add(offset),
+            Bound::Excluded(&max) => Some(
+                max.checked_add(offset)
+                    .map_or(max, |max| max.saturating_sub(1)),
+            ),
+            Bound::Unbounded => None,
+        };
+        PathLength {
+            min_nodes,
+            max_nodes,
+        }
+    }
+
+    // Check whether a path with `nodes` nodes is within bounds.
+    pub fn contains_nodes(&self, nodes: usize) -> bool {
+        nodes >= self.min_nodes && self.max_nodes.is_none_or(|max_nodes| nodes <= max_nodes)
+    }
+
+    // Check whether a path with `edges` edges is within bounds.
+    pub fn contains_edges(&self, edges: usize) -> bool {
+        self.contains_nodes(edges + 1)
+    }
+}
+
+impl Default for PathLength {
+    fn default() -> Self {
+        Self::any()
+    }
+}
+
+// Predicate deciding from a node and its data whether a path may enter the node.
+pub(crate) type NodeFilter<'a, T, N> = Box<dyn Fn(&T, Option<&N>) -> bool + Send + Sync + 'a>;
+
+// Restrictions applied while searching, so excluded branches are never explored.
+pub(crate) struct Constraints<'a, T: Eq + Hash + Clone, N> {
+    pub(crate) length: PathLength,
+    pub(crate) must_visit: HashSet<T>,
+    pub(crate) avoid_nodes: HashSet<T>,
+    // Forbidden edges keyed by their source node.
+    pub(crate) avoid_edges: HashMap<T, HashSet<T>>,
+    // Order in which neighbors are explored. Without one, neighbors follow insertion order.
+    pub(crate) order: Option<fn(&T, &T) -> Ordering>,
+    pub(crate) node_filter: Option<NodeFilter<'a, T, N>>,
+}
+
+impl<'a, T, N> Constraints<'a, T, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn new(length: PathLength) -> Self {
+        Constraints {
+            length,
+            must_visit: HashSet::new(),
+            avoid_nodes: HashSet::new(),
+            avoid_edges: HashMap::new(),
+            order: None,
+            node_filter: None,
+        }
+    }
+
+    // Limit used by find_paths_with_max_steps, which counts nodes and always accepts a start
+    // that is also the end.
+    pub(crate) fn max_steps(max_steps: usize) -> Self {
+        Self::new(PathLength::nodes(..=max_steps.max(1)))
+    }
+
+    // Check whether the search may enter a node that is not in the graph.
+    fn admits_missing(&self, node: &T) -> bool {
+        !self.avoid_nodes.contains(node)
+            && self
+                .node_filter
+                .as_ref()
+                .is_none_or(|node_filter| node_filter(node, None))
+    }
+}
+
+// Constraints translated to node ids once the graph to search is known.
+struct Rules<'a, T: Eq + Hash + Clone, N> {
+    constraints: Constraints<'a, T, N>,
+    must_visit: BitSet,
+    avoid_nodes: BitSet,
+    avoid_edges: HashSet<(NodeId, NodeId)>,
+}
+
+impl<'a, T, N> Rules<'a, T, N>
+where
+    T: Eq + Hash + Clone,
+{
+    fn new<E>(graph: View<'_, T, E, N>, constraints: Constraints<'a, T, N>) -> Self {
+        let ids = |nodes: &HashSet<T>| {
+            let mut set = BitSet::new(graph.id_bound());
+            for id in nodes.iter().filter_map(|node| graph.id(node)) {
+                set.insert(id);
+            }
+            set
+        };
+        let avoid_edges = constraints
+            .avoid_edges
+            .iter()
+            .flat_map(|(node, neighbors)| neighbors.iter().map(move |neighbor| (node, neighbor)))
+            .filter_map(|(node, neighbor)| Some((graph.id(node)?, graph.id(neighbor)?)))
+            .collect();
+        Rules {
+            must_visit: ids(&constraints.must_visit),
+            avoid_nodes: ids(&constraints.avoid_nodes),
+            avoid_edges,
+            constraints,
+        }
+    }
+
+    // Check whether the search may enter `node`.
+    fn admits<E>(&self, graph: View<'_, T, E, N>, node: NodeId) -> bool {
+        !self.avoid_nodes.contains(node)
+            && self
+                .constraints
+                .node_filter
+                .as_ref()
+                .is_none_or(|node_filter| {
+                    node_filter(graph.node_at(node), graph.node_data_at(node))
+                })
+    }
+
+    // Check whether the search may step from `node` to `neighbor`.
+    fn allows(&self, node: NodeId, neighbor: NodeId) -> bool {
+        self.avoid_edges.is_empty() || !self.avoid_edges.contains(&(node, neighbor))
+    }
+}
+
+// Where a path search is allowed to finish.
+#[derive(Clone)]
+pub(crate) enum Goal<T: Eq + Hash + Clone> {
+    End(T),
+    // Any of the targets. With `pass_through`, a path that reaches a target may continue
+    // past it towards another one.
+    Targets {
+        targets: Arc<HashSet<T>>,
+        pass_through: bool,
+    },
+}
+
+impl<T> Goal<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn contains(&self, node: &T) -> bool {
+        match self {
+            Goal::End(end) => end == node,
+            Goal::Targets { targets, .. } => targets.contains(node),
+        }
+    }
+
+    fn nodes(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        match self {
+            Goal::End(end) => Box::new(std::iter::once(end)),
+            Goal::Targets { targets, .. } => Box::new(targets.iter()),
+        }
+    }
+
+    fn passes_through(&self) -> bool {
+        matches!(
+            self,
+            Goal::Targets {
+                pass_through: true,
+                ..
+            }
+        )
+    }
+}
+
+// Distance from nodes to the goal, found by a breadth-first search over reversed edges.
+// Nodes that cannot reach the goal are missing, so the path search never enters them,
+// and a branch is cut as soon as the goal is too far away for the remaining length budget.
+// Only the part of the graph a search from `sources` could use is covered: nodes further
+// from the goal than `max_nodes` allows are missing too.
+pub(crate) struct Reachability {
+    goal: BitSet,
+    // Indexed by node id, one more than the distance, or 0 where the goal cannot be reached.
+    // Zeroed memory is cheap to allocate even for a large graph, so this costs little more
+    // than the nodes the search actually reaches.
+    distances: Vec<u32>,
+}
+
+impl Reachability {
+    pub(crate) fn to<T, E, N, I>(
+        graph: View<'_, T, E, N>,
+        sources: I,
+        goal: &Goal<T>,
+        max_nodes: Option<usize>,
+    ) -> Self
+    where
+        T: Eq + Hash + Clone,
+        I: IntoIterator<Item = NodeId>,
+    {
+        let mut targets = BitSet::new(graph.id_bound());
+        for end in goal.nodes().filter_map(|node| graph.id(node)) {
+            targets.insert(end);
+        }
+        let max_depth = max_nodes.map(|max_nodes| max_nodes.saturating_sub(1));
+
+        // Without an index of incoming edges, reverse the edges a search from the sources could
+        // take: those within `max_depth` of a source that do not leave the goal, unless paths
+        // pass through it.
+        let reversed = (!graph.has_reverse_index()).then(|| {
+            let mut reversed: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+            let mut search = BreadthFirst::new(graph.id_bound(), sources).max_depth(max_depth);
+            while search
+                .next_with(|node| {
+                    let reversed = &mut reversed;
+                    let expand = goal.passes_through() || !targets.contains(node);
+                    let neighbors = expand.then(|| graph.neighbors(node)).into_iter().flatten();
+                    neighbors.inspect(move |neighbor| {
+                        reversed.entry(*neighbor).or_default().push(node);
+                    })
+                })
+                .is_some()
+            {}
+            reversed
+        });
+        let predecessors = |node: NodeId| -> Box<dyn Iterator<Item = NodeId> + '_> {
+            match &reversed {
+                Some(reversed) => Box::new(reversed.get(&node).into_iter().flatten().copied()),
+                None => graph.predecessors(node),
+            }
+        };
+
+        let ends = goal.nodes().filter_map(|node| graph.id(node));
+        let mut search = BreadthFirst::new(graph.id_bound(), ends).max_depth(max_depth);
+        let mut distances = vec![0; graph.id_bound()];
+        while let Some(reached) = search.next_with(predecessors) {
+            // Depths stay below the number of nodes, which fits in a NodeId.
+            distances[reached.node as usize] = reached.depth as u32 + 1;
+        }
+
+        Reachability {
+            goal: targets,
+            distances,
+        }
+    }
+
+    // Check whether `node` is part of the goal.
+    pub(crate) fn is_goal(&self, node: NodeId) -> bool {
+        self.goal.contains(node)
+    }
+
+    // Number of edges on the shortest path from `node` to the goal, if there is one.
+    pub(crate) fn distance(&self, node: NodeId) -> Option<usize> {
+        let distance = self.distances[node as usize];
+        (distance != 0).then(|| distance as usize - 1)
+    }
+}
+
+// Largest total weight a path may have, checked on every step so that a branch is cut as
+// soon as it costs too much. Only sound without negative weights, which could bring the
+// total of a branch back down.
+pub(crate) struct CostLimit<E> {
+    max_cost: E,
+    // Total weight of the current path up to each of its nodes after the start.
+    costs: Vec<E>,
+    // Add a weight to a total, None standing for the empty total, unless the sum is over the
+    // maximum. Taken from the Weight bound when the limit is set, which the search does not have.
+    add: fn(Option<&E>, &E, &E) -> Option<E>,
+}
+
+impl<E> CostLimit<E> {
+    // Total weight of the current path once it steps over an edge with `weight`, if that stays
+    // within the limit.
+    fn step(&self, weight: &E) -> Option<E> {
+        (self.add)(self.costs.last(), weight, &self.max_cost)
+    }
+}
+
+// Cursor over the neighbors of a node that are still to be explored, each with the data of
+// the edge leading to it.
+enum Cursor<'a, E> {
+    Unordered(EdgesFrom<'a, E>),
+    Sorted(vec::IntoIter<(NodeId, &'a E)>),
+}
+
+impl<'a, E> Iterator for Cursor<'a, E> {
+    type Item = (NodeId, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Cursor::Unordered(neighbors) => neighbors.next(),
+            Cursor::Sorted(neighbors) => neighbors.next(),
+        }
+    }
+}
+
+// Depth-first search over all simple paths from a start to a goal, shared by every path enumeration.
+// The search is driven by a heap-allocated stack of frames instead of recursion,
+// so paths are produced one at a time and long paths cannot overflow the thread stack.
+// It runs on node ids and only clones nodes when a path is handed out.
+pub(crate) struct PathSearch<'a, T: Eq + Hash + Clone, E, N> {
+    graph: View<'a, T, E, N>,
+    start: Option<T>,
+    goal: Goal<T>,
+    // Nodes needed after the last waypoint: 1 unless the end is a waypoint itself.
+    // None for a set of targets, which may also be waypoints.
+    end_needed: Option<usize>,
+    rules: Rules<'a, T, N>,
+    reachability: Arc<Reachability>,
+    // One frame per node of `path`, holding the cursor over that node's remaining neighbors.
+    // A frame without a cursor is a dead end that only waits to be popped.
+    frames: Vec<Option<Cursor<'a, E>>>,
+    path: Vec<NodeId>,
+    visited: BitSet,
+    // Number of `must_visit` nodes on the current path.
+    waypoints: usize,
+    // A start that is not in the graph but is the goal, forming a path on its own.
+    lone_start: Option<T>,
+    // Copy of the current path as nodes, lent out by next_path. Only its first `synced` nodes
+    // are up to date: nodes are cloned when a path is lent, and only those pushed since the
+    // previous one, so counting never clones and each step is cloned at most once.
+    output: Vec<T>,
+    synced: usize,
+    cost_limit: Option<CostLimit<E>>,
+}
+
+impl<'a, T, E, N> PathSearch<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn new(
+        graph: View<'a, T, E, N>,
+        start: T,
+        end: T,
+        constraints: Constraints<'a, T, N>,
+    ) -> Self {
+        let goal = Goal::End(end);
+        let reachability = Arc::new(Reachability::to(
+            graph,
+            graph.id(&start),
+            &goal,
+            constraints.length.max_nodes,
+        ));
+        Self::towards(graph, start, goal, constraints, reachability)
+    }
+
+    // Search towards an arbitrary goal, reusing reachability computed for it.
+    pub(crate) fn towards(
+        graph: View<'a, T, E, N>,
+        start: T,
+        goal: Goal<T>,
+        constraints: Constraints<'a, T, N>,
+        reachability: Arc<Reachability>,
+    ) -> Self {
+        let end_needed = match &goal {
+            Goal::End(end) => Some(usize::from(!constraints.must_visit.contains(end))),
+            Goal::Targets { .. } => None,
+        };
+        PathSearch {
+            graph,
+            start: Some(start),
+            goal,
+            end_needed,
+            rules: Rules::new(graph, constraints),
+            reachability,
+            frames: Vec::new(),
+            path: Vec::new(),
+            visited: BitSet::new(graph.id_bound()),
+            waypoints: 0,
+            lone_start: None,
+            output: Vec::new(),
+            synced: 0,
+            cost_limit: None,
+        }
+    }
+
+    // Resume a search whose path is fixed to start with `prefix`. Only the last node of the
+    // prefix branches, so the search covers exactly the paths extending it.
+    // The prefix must be a simple path that does not already reach the end.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn with_prefix(
+        graph: View<'a, T, E, N>,
+        prefix: Vec<NodeId>,
+        end: T,
+        constraints: Constraints<'a, T, N>,
+        reachability: Arc<Reachability>,
+    ) -> Self {
+        let mut search = Self::towards(
+            graph,
+            graph.node_at(prefix[0]).clone(),
+            Goal::End(end),
+            constraints,
+            reachability,
+        );
+        search.start = None;
+        for node in prefix {
+            search.push(node);
+        }
+        let last = search.frames.len().saturating_sub(1);
+        for frame in &mut search.frames[..last] {
+            *frame = None;
+        }
+        search
+    }
+
+    // Cut every branch whose total weight goes over `max_cost`. The graph must not have
+    // negative weights.
+    pub(crate) fn max_cost(mut self, max_cost: E) -> Self
+    where
+        E: Weight,
+    {
+        self.cost_limit = Some(CostLimit {
+            max_cost,
+            costs: Vec::new(),
+            add: |cost, weight, max_cost| {
+                let cost = cost.map_or(*weight, |cost| *cost + *weight);
+                (cost <= *max_cost).then_some(cost)
+            },
+        });
+        self
+    }
+
+    // Total weight of the path found by the last call to advance, if the search has a cost
+    // limit and the path has an edge.
+    pub(crate) fn cost(&self) -> Option<&E> {
+        self.cost_limit.as_ref()?.costs.last()
+    }
+
+    // Extend the current path with `node` and push its frame.
+    // Returns true if the path now reaches the end and satisfies every constraint.
+    fn push(&mut self, node: NodeId) -> bool {
+        let constraints = &self.rules.constraints;
+        if self.rules.must_visit.contains(node) {
+            self.waypoints += 1;
+        }
+        let len = self.path.len() + 1;
+        let missing = constraints.must_visit.len() - self.waypoints;
+
+        let reached = self.reachability.is_goal(node);
+        // Every missing waypoint needs one more node, and so does the end unless it is a waypoint.
+        let needed = match self.end_needed {
+            Some(end_needed) => missing + end_needed,
+            None => missing.max(1),
+        };
+        let exhausted = constraints
+            .length
+            .max_nodes
+            .is_some_and(|max_nodes| len + needed > max_nodes);
+
+        let cursor = if (reached && !self.goal.passes_through()) || exhausted {
+            None
+        } else {
+            let graph = self.graph;
+            Some(match constraints.order {
+                Some(order) => {
+                    let mut sorted: Vec<_> = graph.edges_from(node).collect();
+                    sorted.sort_by(|(a, _), (b, _)| order(graph.node_at(*a), graph.node_at(*b)));
+                    Cursor::Sorted(sorted.into_iter())
+                }
+                None => Cursor::Unordered(graph.edges_from(node)),
+            })
+        };
+
+        self.visited.insert(node);
+        self.path.push(node);
+        self.frames.push(cursor);
+
+        reached && missing == 0 && constraints.length.contains_nodes(len)
+    }
+
+    // Drop the last node of the current path together with its frame.
+    fn pop(&mut self) {
+        self.frames.pop();
+        if let Some(node) = self.path.pop() {
+            if self.rules.must_visit.contains(node) {
+                self.waypoints -= 1;
+            }
+            self.visited.remove(node);
+        }
+        self.synced = self.synced.min(self.path.len());
+        if let Some(cost_limit) = &mut self.cost_limit {
+            cost_limit.costs.truncate(self.path.len().saturating_sub(1));
+        }
+    }
+
+    // A start that is not in the graph can only form the single-node path, and only if it
+    // is the goal.
+    fn lone_path(&mut self, start: T) -> bool {
+        let constraints = &self.rules.constraints;
+        let waypoints = usize::from(constraints.must_visit.contains(&start));
+        let found = self.goal.contains(&start)
+            && constraints.admits_missing(&start)
+            && constraints.must_visit.len() == waypoints
+            && constraints.length.contains_nodes(1);
+        if found {
+            self.lone_start = Some(start);
+        }
+        found
+    }
+
+    // Advance to the next path without translating it back to nodes.
+    fn advance(&mut self) -> bool {
+        if let Some(start) = self.start.take() {
+            let Some(start) = self.graph.id(&start) else {
+                return self.lone_path(start);
+            };
+            if !self.rules.admits(self.graph, start) || self.reachability.distance(start).is_none()
+            {
+                return false;
+            }
+            if self.push(start) {
+                return true;
+            }
+        }
+
+        while let Some(frame) = self.frames.last_mut() {
+            let (graph, visited, rules) = (self.graph, &self.visited, &self.rules);
+            let (reachability, cost_limit) = (&self.reachability, &self.cost_limit);
+            let node = self.path[self.path.len() - 1];
+            // Length of the path once it steps to the neighbor.
+            let len = self.path.len() + 1;
+            // Total weight of the path once it steps to the neighbor, under a cost limit.
+            let mut cost = None;
+            let neighbor = frame.as_mut().and_then(|cursor| {
+                cursor.find(|(neighbor, weight)| {
+                    !visited.contains(*neighbor)
+                        && rules.allows(node, *neighbor)
+                        && rules.admits(graph, *neighbor)
+                        && reachability.distance(*neighbor).is_some_and(|distance| {
+                            rules
+                                .constraints
+                                .length
+                                .max_nodes
+                                .is_none_or(|max_nodes| len + distance <= max_nodes)
+                        })
+                        && cost_limit.as_ref().is_none_or(|cost_limit| {
+                            cost = cost_limit.step(weight);
+                            cost.is_some()
+                        })
+                })
+            });
+
+            match neighbor {
+                Some((neighbor, _)) => {
+                    if let (Some(cost_limit), Some(cost)) = (&mut self.cost_limit, cost) {
+                        cost_limit.costs.push(cost);
+                    }
+                    if self.push(neighbor) {
+                        return true;
+                    }
+                }
+                None => self.pop(),
+            }
+        }
+
+        false
+    }
+
+    // Nodes of the path found by the last call to advance.
+    fn found(&self) -> impl Iterator<Item = &T> {
+        let graph = self.graph;
+        self.lone_start
+            .iter()
+            .chain(self.path.iter().map(move |node| graph.node_at(*node)))
+    }
+
+    // Advance to the next path, which stays borrowed until the search moves on.
+    pub(crate) fn next_path(&mut self) -> Option<&[T]> {
+        if !self.advance() {
+            return None;
+        }
+        if let Some(start) = &self.lone_start {
+            self.output = vec![start.clone()];
+            return Some(&self.output);
+        }
+        let graph = self.graph;
+        self.output.truncate(self.synced);
+        self.output.extend(
+            self.path[self.synced..]
+                .iter()
+                .map(|node| graph.node_at(*node).clone()),
+        );
+        self.synced = self.path.len();
+        Some(&self.output)
+    }
+
+    // Advance to the next path and return the ids of its nodes. A start that is not in the
+    // graph gives no ids.
+    pub(crate) fn next_ids(&mut self) -> Option<&[NodeId]> {
+        self.advance().then_some(self.path.as_slice())
+    }
+
+    // Advance to the next path and return a copy of it.
+    pub(crate) fn next_owned(&mut self) -> Option<Vec<T>> {
+        self.advance().then(|| self.found().cloned().collect())
+    }
+
+    // Count the remaining paths without cloning any of them.
+    pub(crate) fn count(mut self) -> u128 {
+        let mut count = 0u128;
+        while self.advance() {
+            count = count.saturating_add(1);
+        }
+        count
+    }
+}
+
+// A path found by find_all_paths_between, tagged with the source it starts from
+// and the target it reaches.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TaggedPath<T> {
+    pub source: T,
+    pub target: T,
+    pub path: Vec<T>,
+}
+
+// Lazy iterator over all simple paths between two nodes.
+pub struct PathsIter<'a, T: Eq + Hash + Clone, E = (), N = ()> {
+    search: PathSearch<'a, T, E, N>,
+}
+
+impl<'a, T, E, N> PathsIter<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn new(
+        graph: View<'a, T, E, N>,
+        start: T,
+        end: T,
+        constraints: Constraints<'a, T, N>,
+    ) -> Self {
+        PathsIter {
+            search: PathSearch::new(graph, start, end, constraints),
+        }
+    }
+}
+
+impl<'a, T, E, N> Iterator for PathsIter<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        self.search.next_owned()
+    }
+}
+
+// Searches shared by Graph and FrozenGraph.
+impl<'a, T, E, N> View<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn paths_iter(self, start: T, end: T) -> PathsIter<'a, T, E, N> {
+        PathsIter::new(self, start, end, Constraints::new(PathLength::any()))
+    }
+
+    pub(crate) fn paths_iter_with_max_steps(
+        self,
+        start: T,
+        end: T,
+        max_steps: usize,
+    ) -> PathsIter<'a, T, E, N> {
+        PathsIter::new(self, start, end, Constraints::max_steps(max_steps))
+    }
+
+    pub(crate) fn find_all_paths_with_edges(self, start: T, end: T) -> Vec<(Vec<T>, Vec<&'a E>)> {
+        self.paths_iter(start, end)
+            .map(|path| {
+                let edges = self.path_edges(&path);
+                (path, edges)
+            })
+            .collect()
+    }
+
+    // Collect the data of the edges along a path found in this graph.
+    fn path_edges(self, path: &[T]) -> Vec<&'a E> {
+        path.windows(2)
+            .filter_map(|edge| self.edge(&edge[0], &edge[1]))
+            .collect()
+    }
+
+    pub(crate) fn paths_between<I>(
+        self,
+        sources: I,
+        targets: &HashSet<T>,
+        pass_through: bool,
+    ) -> Vec<TaggedPath<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let goal = Goal::Targets {
+            targets: Arc::new(targets.clone()),
+            pass_through,
+        };
+        let sources: Vec<T> = sources.into_iter().collect();
+        let ids = sources.iter().filter_map(|source| self.id(source));
+        let reachability = Arc::new(Reachability::to(self, ids, &goal, None));
+
+        let mut paths = Vec::new();
+        for source in sources {
+            let mut search = PathSearch::towards(
+                self,
+                source.clone(),
+                goal.clone(),
+                Constraints::new(PathLength::any()),
+                reachability.clone(),
+            );
+            while let Some(path) = search.next_owned() {
+                paths.push(TaggedPath {
+                    source: source.clone(),
+                    target: path[path.len() - 1].clone(),
+                    path,
+                });
+            }
+        }
+        paths
+    }
+
+    pub(crate) fn count_paths(self, start: T, end: T) -> u128 {
+        match self.count_paths_dag(start.clone(), end.clone()) {
+            Some(count) => count,
+            None => PathSearch::new(self, start, end, Constraints::new(PathLength::any())).count(),
+        }
+    }
+
+    pub(crate) fn count_paths_dag(self, start: T, end: T) -> Option<u128> {
+        let Some(start) = self.id(&start) else {
+            return Some(u128::from(start == end));
+        };
+        let end = self.id(&end);
+
+        // Iterative post-order DFS. Nodes on the stack are unfinished, so reaching one again is a cycle.
+        let mut counts: Vec<Option<u128>> = vec![None; self.id_bound()];
+        let mut on_stack = BitSet::new(self.id_bound());
+        let mut stack = vec![(start, self.neighbors(start))];
+        on_stack.insert(start);
+
+        while let Some((node, neighbors)) = stack.last_mut() {
+            let node = *node;
+            if Some(node) == end {
+                counts[node as usize] = Some(1);
+            } else if let Some(neighbor) =
+                neighbors.find(|neighbor| *neighbor != node && counts[*neighbor as usize].is_none())
+            {
+                if !on_stack.insert(neighbor) {
+                    return None;
+                }
+                stack.push((neighbor, self.neighbors(neighbor)));
+                continue;
+            } else {
+                let count = self.neighbors(node).fold(0u128, |count, neighbor| {
+                    count.saturating_add(counts[neighbor as usize].unwrap_or(0))
+                });
+                counts[node as usize] = Some(count);
+            }
+            on_stack.remove(node);
+            stack.pop();
+        }
+
+        counts[start as usize]
+    }
+}
+
+// Searches over the weights of a graph's edges.
+impl<T, E, N> View<'_, T, E, N>
+where
+    T: Eq + Hash + Clone,
+    E: Weight,
+{
+    pub(crate) fn weight(self, vector_x: &T, vector_y: &T) -> Option<E> {
+        self.edge(vector_x, vector_y).copied()
+    }
+
+    pub(crate) fn path_cost(self, path: &[T]) -> Option<E> {
+        path.windows(2).try_fold(E::zero(), |cost, edge| {
+            Some(cost + self.weight(&edge[0], &edge[1])?)
+        })
+    }
+
+    pub(crate) fn find_all_weighted_paths(self, start: T, end: T) -> Vec<(Vec<T>, E)> {
+        self.paths_iter(start, end)
+            .filter_map(|path| {
+                let cost = self.path_cost(&path)?;
+                Some((path, cost))
+            })
+            .collect()
+    }
+
+    // Without negative weights, a branch only gets costlier, so it is cut as soon as it goes
+    // over `max_cost`. Otherwise every path is found and the costly ones are dropped.
+    pub(crate) fn find_weighted_paths_with_max_cost(
+        self,
+        start: T,
+        end: T,
+        max_cost: E,
+    ) -> Vec<(Vec<T>, E)> {
+        if self.negative_edge().is_some() {
+            return self
+                .find_all_weighted_paths(start, end)
+                .into_iter()
+                .filter(|(_, cost)| *cost <= max_cost)
+                .collect();
+        }
+        let constraints = Constraints::new(PathLength::any());
+        let mut search = PathSearch::new(self, start, end, constraints).max_cost(max_cost);
+        let mut paths = Vec::new();
+        while let Some(path) = search.next_owned() {
+            // A path of one node has no edge to cut it by.
+            let cost = search.cost().copied().unwrap_or_else(E::zero);
+            if cost <= max_cost {
+                paths.push((path, cost));
+            }
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+    use std::sync::atomic::{self, AtomicUsize};
+
+    use super::*;
+    use crate::graph::Graph;
+    use crate::search::Search;
+
+    #[test]
+    fn test_paths_iter() {
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 4);
+        graph.add_edge(1, 3);
+        graph.add_edge(3, 4);
+        let mut paths: Vec<_> = graph.paths_iter(1, 4).collect();
+        paths.sort();
+        assert_eq!(paths, vec![vec![1, 2, 4], vec![1, 3, 4]]);
+        assert_eq!(graph.paths_iter(1, 4).take(1).count(), 1);
+        assert_eq!(graph.paths_iter(1, 1).collect::<Vec<_>>(), vec![vec![1]]);
+    }
+
+    #[test]
+    fn test_paths_iter_matches_find_all_paths() {
+        let mut graph = Graph::new(Some(true));
+        for (x, y) in [
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4),
+            (2, 4),
+            (4, 1),
+            (3, 5),
+            (5, 4),
+        ] {
+            graph.add_edge(x, y);
+        }
+        let paths: Vec<_> = graph.paths_iter(1, 4).collect();
+        assert_eq!(paths.len(), 5);
+        assert_eq!(paths, graph.find_all_paths(1, 4));
+
+        let paths: Vec<_> = graph.paths_iter_with_max_steps(1, 4, 3).collect();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths, graph.find_paths_with_max_steps(1, 4, 3));
+    }
+
+    #[test]
+    fn test_find_all_paths_long_chain() {
+        // Long enough to overflow the thread stack with a recursive search.
+        let n = 1_000_000;
+        let mut graph = Graph::new(Some(false));
+        for i in 0..n - 1 {
+            graph.add_edge(i, i + 1);
+        }
+
+        let paths = graph.find_all_paths(0, n - 1);
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].iter().copied().eq(0..n));
+
+        assert!(graph.find_paths_with_max_steps(0, n - 1, n - 1).is_empty());
+    }
+
+    #[test]
+    fn test_visit_paths() {
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 4);
+        graph.add_edge(1, 3);
+        graph.add_edge(3, 4);
+
+        let mut visited = Vec::new();
+        let flow = graph.visit_paths(1, 4, |path| {
+            visited.push(path.to_vec());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(visited, graph.find_all_paths(1, 4));
+
+        let mut visited = Vec::new();
+        let flow = graph.visit_paths(1, 4, |path| {
+            visited.push(path.to_vec());
+            ControlFlow::Break(())
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(visited.len(), 1);
+    }
+
+    thread_local! {
+        static CLONES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    // Node that counts how often it is cloned on this thread.
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Counted(u32);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.set(CLONES.get() + 1);
+            Counted(self.0)
+        }
+    }
+
+    #[test]
+    fn test_visit_paths_clones_each_step_once() {
+        // A ladder of 8 diamonds has 256 paths of 17 nodes from end to end.
+        let mut graph = Graph::new(Some(true));
+        for i in 0..8 {
+            for (x, y) in [(0, 1), (0, 2), (1, 3), (2, 3)] {
+                graph.add_edge(Counted(3 * i + x), Counted(3 * i + y));
+            }
+        }
+        let (start, end) = (Counted(0), Counted(24));
+        CLONES.set(0);
+
+        let mut nodes = 0;
+        let _ = graph.visit_paths(start, end, |path| {
+            nodes += path.len();
+            ControlFlow::Continue(())
+        });
+        assert_eq!(nodes, 256 * 17);
+        // The search tree has one node per step: 1 + 2 + 2 + 4 + 4 + ... + 256 + 256.
+        assert_eq!(CLONES.get(), 1 + 4 * (256 - 1));
+    }
+
+    #[test]
+    fn test_count_paths() {
+        let mut graph = Graph::new(Some(false));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 4);
+        graph.add_edge(1, 3);
+        graph.add_edge(3, 4);
+        graph.add_edge(2, 3);
+        assert_eq!(graph.count_paths(1, 4), 4);
+        assert_eq!(
+            graph.count_paths(1, 4),
+            graph.find_all_paths(1, 4).len() as u128
+        );
+        assert_eq!(graph.count_paths_with_max_steps(1, 4, 3), 2);
+        assert_eq!(graph.count_paths(1, 1), 1);
+        assert_eq!(graph.count_paths(1, 5), 0);
+        assert_eq!(graph.count_paths_dag(1, 4), None);
+    }
+
+    #[test]
+    fn test_count_paths_dag() {
+        // A ladder of diamonds doubles the path count at every rung.
+        let mut graph = Graph::new(Some(true));
+        for i in 0..100u32 {
+            graph.add_edge(3 * i, 3 * i + 1);
+            graph.add_edge(3 * i, 3 * i + 2);
+            graph.add_edge(3 * i + 1, 3 * i + 3);
+            graph.add_edge(3 * i + 2, 3 * i + 3);
+        }
+        assert_eq!(graph.count_paths_dag(0, 300), Some(1 << 100));
+        assert_eq!(graph.count_paths(0, 300), 1 << 100);
+        assert_eq!(graph.count_paths_dag(0, 6), Some(4));
+        assert_eq!(graph.count_paths_dag(3, 0), Some(0));
+        assert_eq!(graph.count_paths_dag(7, 7), Some(1));
+        // A self-loop cannot be part of a path, so the graph is still counted as acyclic.
+        graph.add_edge(150, 150);
+        assert_eq!(graph.count_paths_dag(0, 300), Some(1 << 100));
+
+        graph.add_edge(6, 4);
+        assert_eq!(graph.count_paths_dag(0, 300), None);
+        assert_eq!(graph.count_paths_dag(7, 300), Some(1 << 97));
+        // The only cycle passes through the end, where the search stops.
+        assert_eq!(graph.count_paths_dag(0, 4), Some(4));
+        assert_eq!(graph.count_paths(0, 4), 4);
+        assert_eq!(graph.count_paths(0, 9), 8);
+        assert_eq!(graph.count_paths_with_max_steps(0, 9, 7), 8);
+    }
+
+    #[test]
+    fn test_path_length() {
+        assert_eq!(PathLength::edges(3..=3), PathLength::nodes(4..=4));
+        assert_eq!(PathLength::edges(2..6), PathLength::nodes(3..=6));
+        assert_eq!(PathLength::edges(..), PathLength::any());
+        assert!(PathLength::edges(2..=5).contains_edges(5));
+        assert!(!PathLength::edges(2..=5).contains_edges(1));
+        assert!(!PathLength::edges(..0).contains_nodes(1));
+        assert!(!PathLength::nodes(..=0).contains_nodes(1));
+        assert_eq!(PathLength::edges(..=usize::MAX), PathLength::any());
+        assert_eq!(PathLength::edges(..usize::MAX).max_nodes, Some(usize::MAX));
+        assert!(PathLength::nodes(..=usize::MAX).contains_edges(3));
+        assert!(!PathLength::edges(usize::MAX..).contains_edges(3));
+    }
+
+    #[test]
+    fn test_find_paths_with_length() {
+        let mut graph = Graph::new(Some(true));
+        for (x, y) in [(1, 2), (2, 3), (3, 4), (1, 3), (2, 4), (1, 4)] {
+            graph.add_edge(x, y);
+        }
+        let paths = graph.find_paths_with_length(1, 4, PathLength::edges(3..=3));
+        assert_eq!(paths, vec![vec![1, 2, 3, 4]]);
+        let mut paths = graph.find_paths_with_length(1, 4, PathLength::edges(2..));
+        paths.sort();
+        assert_eq!(paths, vec![vec![1, 2, 3, 4], vec![1, 2, 4], vec![1, 3, 4]]);
+        let paths = graph.find_paths_with_length(1, 4, PathLength::nodes(..=2));
+        assert_eq!(paths, vec![vec![1, 4]]);
+        assert_eq!(
+            graph.find_paths_with_length(1, 1, PathLength::edges(1..)),
+            Vec::<Vec<i32>>::new()
+        );
+        assert_eq!(graph.find_paths_with_max_steps(1, 1, 0), vec![vec![1]]);
+    }
+
+    #[test]
+    fn test_reachability() {
+        let mut graph = Graph::new(Some(true));
+        for (x, y) in [(1, 2), (2, 3), (3, 4), (1, 5), (5, 6), (6, 5), (2, 4)] {
+            graph.add_edge(x, y);
+        }
+        let reachability = Reachability::to(graph.view(), graph.id(&1), &Goal::End(4), None);
+        let distance = |reachability: &Reachability, node| reachability.distance(graph.id(&node)?);
+        assert_eq!(distance(&reachability, 4), Some(0));
+        assert_eq!(distance(&reachability, 3), Some(1));
+        assert_eq!(distance(&reachability, 2), Some(1));
+        assert_eq!(distance(&reachability, 1), Some(2));
+        assert_eq!(distance(&reachability, 5), None);
+        assert_eq!(distance(&reachability, 6), None);
+
+        let mut indexed = graph.clone();
+        indexed.enable_reverse_index();
+        let indexed_reachability =
+            Reachability::to(indexed.view(), indexed.id(&1), &Goal::End(4), None);
+        for node in [1, 2, 3, 4, 5, 6] {
+            assert_eq!(
+                distance(&indexed_reachability, node),
+                distance(&reachability, node)
+            );
+        }
+
+        // The branch through 5 and 6 is never entered, and neither is 3 once the budget is spent.
+        assert_eq!(
+            graph.find_paths_with_max_steps(1, 4, 3),
+            vec![vec![1, 2, 4]]
+        );
+        assert_eq!(graph.count_paths(1, 4), 2);
+        assert_eq!(graph.count_paths(5, 4), 0);
+
+        let graph = Graph::new(Some(false));
+        // An end outside the graph has no id, but is still the only path from itself.
+        assert!(Reachability::to(graph.view(), None, &Goal::End(1), None)
+            .distances
+            .is_empty());
+        assert_eq!(graph.find_all_paths(1, 1), vec![vec![1]]);
+        assert_eq!(graph.count_paths(1, 1), 1);
+        assert_eq!(graph.query(1, 1).avoid_nodes([1]).count_paths(), 0);
+    }
+
+    #[test]
+    fn test_reachability_stays_local() {
+        let mut graph = Graph::new(Some(true));
+        for i in 0..100_000 {
+            graph.add_edge(i, i + 1);
+        }
+        let covered = |reachability: Reachability| {
+            reachability
+                .distances
+                .iter()
+                .filter(|distance| **distance != 0)
+                .count()
+        };
+        // The search from 0 stops at the end, so nothing past it is looked at.
+        let view = graph.view();
+        assert_eq!(
+            covered(Reachability::to(view, graph.id(&0), &Goal::End(1), None)),
+            2
+        );
+        // Nodes further from the start than the bound are never reached, so only the end is.
+        let reachability = Reachability::to(view, graph.id(&0), &Goal::End(50_000), Some(4));
+        assert_eq!(covered(reachability), 1);
+
+        graph.enable_reverse_index();
+        let view = graph.view();
+        let reachability = Reachability::to(view, graph.id(&0), &Goal::End(50_000), Some(4));
+        assert_eq!(covered(reachability), 4);
+        assert_eq!(graph.paths_iter(0, 1).next(), Some(vec![0, 1]));
+        assert_eq!(graph.count_paths_with_max_steps(0, 3, 4), 1);
+    }
+
+    #[test]
+    fn test_max_cost_cuts_costly_branches() {
+        // A costly edge from 0 leads into a clique with many paths on to 9.
+        let mut graph = Graph::new_weighted(Some(false));
+        graph.add_weighted_edge(0, 1, 100);
+        graph.add_weighted_edge(0, 9, 1);
+        graph.add_weighted_edge(7, 9, 1);
+        for x in 1..=7 {
+            for y in x + 1..=7 {
+                graph.add_weighted_edge(x, y, 1);
+            }
+        }
+        let entered = |max_cost: Option<i32>| {
+            let count = AtomicUsize::new(0);
+            let mut constraints = Constraints::new(PathLength::any());
+            constraints.node_filter = Some(Box::new(|_, _| {
+                count.fetch_add(1, atomic::Ordering::Relaxed);
+                true
+            }));
+            let search = PathSearch::new(graph.view(), 0, 9, constraints);
+            match max_cost {
+                Some(max_cost) => search.max_cost(max_cost).count(),
+                None => search.count(),
+            };
+            count.into_inner()
+        };
+        assert!(entered(Some(10)) < 5);
+        assert!(entered(None) > 1000);
+        assert_eq!(
+            graph.find_weighted_paths_with_max_cost(0, 9, 10),
+            vec![(vec![0, 9], 1)]
+        );
+        let mut within: Vec<_> = graph
+            .find_all_weighted_paths(0, 9)
+            .into_iter()
+            .filter(|(_, cost)| *cost <= 105)
+            .collect();
+        within.sort();
+        let mut paths = graph.find_weighted_paths_with_max_cost(0, 9, 105);
+        paths.sort();
+        assert_eq!(paths, within);
+
+        // A negative weight can bring a costly branch back under the limit.
+        graph.add_weighted_edge(1, 9, -99);
+        assert!(graph
+            .find_weighted_paths_with_max_cost(0, 9, 1)
+            .contains(&(vec![0, 1, 9], 1)));
+    }
+
+    #[test]
+    fn test_find_all_paths_between() {
+        let mut graph = Graph::new(Some(true));
+        for (x, y) in [(1, 3), (2, 3), (3, 4), (4, 5), (3, 5), (6, 4)] {
+            graph.add_edge(x, y);
+        }
+        let targets = HashSet::from([4, 5]);
+
+        let mut paths = graph.find_all_paths_between([1, 2, 6], &targets);
+        paths.sort_by(|a, b| a.path.cmp(&b.path));
+        let tagged: Vec<_> = paths.iter().map(|p| (p.source, p.target)).collect();
+        assert_eq!(tagged, vec![(1, 4), (1, 5), (2, 4), (2, 5), (6, 4)]);
+        assert_eq!(paths[0].path, vec![1, 3, 4]);
+
+        let mut paths = graph.find_all_paths_between_through_targets([1, 6], &targets);
+        paths.sort_by(|a, b| a.path.cmp(&b.path));
+        let found: Vec<_> = paths.into_iter().map(|p| p.path).collect();
+        assert_eq!(
+            found,
+            vec![
+                vec![1, 3, 4],
+                vec![1, 3, 4, 5],
+                vec![1, 3, 5],
+                vec![6, 4],
+                vec![6, 4, 5]
+            ]
+        );
+
+        let paths = graph.find_all_paths_between([4], &targets);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].path, vec![4]);
+    }
+
+    #[test]
+    fn test_find_all_paths_sorted() {
+        let mut graph = Graph::new(Some(false));
+        for (x, y) in [
+            (1, 5),
+            (5, 9),
+            (1, 3),
+            (3, 9),
+            (1, 7),
+            (7, 9),
+            (3, 7),
+            (1, 2),
+            (2, 9),
+        ] {
+            graph.add_edge(x, y);
+        }
+        let paths = graph.find_all_paths_sorted(1, 9);
+        assert_eq!(
+            paths,
+            vec![
+                vec![1, 2, 9],
+                vec![1, 3, 7, 9],
+                vec![1, 3, 9],
+                vec![1, 5, 9],
+                vec![1, 7, 3, 9],
+                vec![1, 7, 9],
+            ]
+        );
+
+        let mut strings = Graph::new(Some(true));
+        for (x, y) in [
+            ("b", "d"),
+            ("a", "d"),
+            ("s", "b"),
+            ("s", "a"),
+            ("s", "c"),
+            ("c", "d"),
+        ] {
+            strings.add_edge(x.to_string(), y.to_string());
+        }
+        let paths = strings.find_all_paths_sorted("s".to_string(), "d".to_string());
+        let second: Vec<_> = paths.iter().map(|path| path[1].as_str()).collect();
+        assert_eq!(second, vec!["a", "b", "c"]);
+    }
+}
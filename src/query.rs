@@ -0,0 +1,262 @@
+use std::hash::Hash;
+use std::ops::ControlFlow;
+
+use crate::paths::{Constraints, PathLength, PathSearch, PathsIter};
+use crate::view::View;
+
+// Builder for path searches with waypoints, exclusions and length bounds.
+// Every option is applied while searching, so excluded regions are never explored.
+pub struct PathQuery<'a, T: Eq + Hash + Clone, E = (), N = ()> {
+    graph: View<'a, T, E, N>,
+    start: T,
+    end: T,
+    constraints: Constraints<'a, T, N>,
+}
+
+impl<'a, T, E, N> PathQuery<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn new(graph: View<'a, T, E, N>, start: T, end: T) -> Self {
+        PathQuery {
+            graph,
+            start,
+            end,
+            constraints: Constraints::new(PathLength::any()),
+        }
+    }
+
+    // Only keep paths that pass through every one of these nodes.
+    pub fn must_visit<I: IntoIterator<Item = T>>(mut self, nodes: I) -> Self {
+        self.constraints.must_visit.extend(nodes);
+        self
+    }
+
+    // Never step on any of these nodes.
+    pub fn avoid_nodes<I: IntoIterator<Item = T>>(mut self, nodes: I) -> Self {
+        self.constraints.avoid_nodes.extend(nodes);
+        self
+    }
+
+    // Never traverse any of these edges. Edges of an undirected graph are avoided both ways.
+    pub fn avoid_edges<I: IntoIterator<Item = (T, T)>>(mut self, edges: I) -> Self {
+        for (vector_x, vector_y) in edges {
+            if !self.graph.is_directed() {
+                self.constraints
+                    .avoid_edges
+                    .entry(vector_y.clone())
+                    .or_default()
+                    .insert(vector_x.clone());
+            }
+            self.constraints
+                .avoid_edges
+                .entry(vector_x)
+                .or_default()
+                .insert(vector_y);
+        }
+        self
+    }
+
+    // Only keep paths whose length is within bounds.
+    pub fn length(mut self, length: PathLength) -> Self {
+        self.constraints.length = length;
+        self
+    }
+
+    // Only step on nodes for which `filter` returns true, given the node and its data.
+    // Filters added by repeated calls must all pass.
+    pub fn filter_nodes<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&T, Option<&N>) -> bool + Send + Sync + 'a,
+    {
+        self.constraints.node_filter = Some(match self.constraints.node_filter.take() {
+            Some(previous) => {
+                Box::new(move |node, data| previous(node, data) && filter(node, data))
+            }
+            None => Box::new(filter),
+        });
+        self
+    }
+
+    // Only keep paths with at least `min_len` edges.
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.constraints.length.min_nodes = PathLength::edges(min_len..).min_nodes;
+        self
+    }
+
+    // Only keep paths with at most `max_len` edges.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.constraints.length.max_nodes = PathLength::edges(..=max_len).max_nodes;
+        self
+    }
+
+    // Explore neighbors in ascending order, so paths come out in the same order on every run.
+    pub fn sorted(mut self) -> Self
+    where
+        T: Ord,
+    {
+        self.constraints.order = Some(T::cmp);
+        self
+    }
+
+    // Lazily iterate over the matching paths.
+    pub fn paths_iter(self) -> PathsIter<'a, T, E, N> {
+        PathsIter::new(self.graph, self.start, self.end, self.constraints)
+    }
+
+    // Collect all matching paths.
+    pub fn find_all_paths(self) -> Vec<Vec<T>> {
+        self.paths_iter().collect()
+    }
+
+    // Count the matching paths without materializing them.
+    pub fn count_paths(self) -> u128 {
+        PathSearch::new(self.graph, self.start, self.end, self.constraints).count()
+    }
+
+    // Call `visitor` with each matching path until it breaks.
+    pub fn visit_paths<F>(self, mut visitor: F) -> ControlFlow<()>
+    where
+        F: FnMut(&[T]) -> ControlFlow<()>,
+    {
+        let mut search = PathSearch::new(self.graph, self.start, self.end, self.constraints);
+        while let Some(path) = search.next_path() {
+            visitor(path)?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::two_routes;
+    use crate::graph::Graph;
+    use crate::search::Search;
+
+    fn sorted(mut paths: Vec<Vec<i32>>) -> Vec<Vec<i32>> {
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_query_must_visit_and_avoid_nodes() {
+        let graph = two_routes(false);
+        let paths = graph
+            .query(1, 6)
+            .must_visit([5])
+            .avoid_nodes([3])
+            .find_all_paths();
+        assert_eq!(paths, vec![vec![1, 2, 5, 6]]);
+
+        let paths = graph.query(1, 6).must_visit([2, 3]).find_all_paths();
+        assert_eq!(paths, vec![vec![1, 3, 5, 2, 4, 6]]);
+
+        assert_eq!(graph.query(1, 6).must_visit([6]).count_paths(), 4);
+        assert_eq!(graph.query(1, 6).avoid_nodes([6]).count_paths(), 0);
+        assert_eq!(graph.query(1, 6).avoid_nodes([1]).count_paths(), 0);
+    }
+
+    #[test]
+    fn test_query_avoid_edges() {
+        let graph = two_routes(false);
+        let paths = graph
+            .query(1, 6)
+            .avoid_edges([(5, 2), (6, 4)])
+            .find_all_paths();
+        assert_eq!(paths, vec![vec![1, 3, 5, 6]]);
+
+        let graph = two_routes(true);
+        let paths = graph.query(1, 6).avoid_edges([(5, 2)]).find_all_paths();
+        assert_eq!(paths.len(), 3);
+        let paths = graph.query(1, 6).avoid_edges([(2, 5)]).find_all_paths();
+        assert_eq!(sorted(paths), vec![vec![1, 2, 4, 6], vec![1, 3, 5, 6]]);
+    }
+
+    #[test]
+    fn test_query_length_bounds() {
+        let graph = two_routes(false);
+        assert_eq!(graph.query(1, 6).count_paths(), 4);
+        let paths = graph.query(1, 6).max_len(3).find_all_paths();
+        assert_eq!(
+            sorted(paths),
+            vec![vec![1, 2, 4, 6], vec![1, 2, 5, 6], vec![1, 3, 5, 6]]
+        );
+        let paths = graph.query(1, 6).min_len(4).find_all_paths();
+        assert_eq!(paths, vec![vec![1, 3, 5, 2, 4, 6]]);
+        assert_eq!(graph.query(1, 6).min_len(3).max_len(3).count_paths(), 3);
+        assert_eq!(
+            graph
+                .query(1, 6)
+                .length(PathLength::edges(3..=3))
+                .count_paths(),
+            3
+        );
+        // The waypoints cannot all fit within the length bound.
+        assert_eq!(
+            graph
+                .query(1, 6)
+                .must_visit([3, 4])
+                .max_len(4)
+                .count_paths(),
+            0
+        );
+        assert_eq!(
+            graph
+                .query(1, 6)
+                .must_visit([3, 4])
+                .max_len(5)
+                .count_paths(),
+            1
+        );
+        assert_eq!(graph.query(1, 6).max_len(usize::MAX).count_paths(), 4);
+        assert_eq!(graph.query(1, 6).min_len(usize::MAX).count_paths(), 0);
+    }
+
+    #[test]
+    fn test_query_sorted() {
+        let graph = two_routes(false);
+        let paths = graph.query(1, 6).sorted().find_all_paths();
+        assert_eq!(
+            paths,
+            vec![
+                vec![1, 2, 4, 6],
+                vec![1, 2, 5, 6],
+                vec![1, 3, 5, 2, 4, 6],
+                vec![1, 3, 5, 6]
+            ]
+        );
+        let paths: Vec<_> = graph.query(1, 6).sorted().max_len(3).paths_iter().collect();
+        assert_eq!(paths[2], vec![1, 3, 5, 6]);
+    }
+
+    #[test]
+    fn test_query_filter_nodes() {
+        let mut graph: Graph<i32, (), &str> = Graph::new_with_data(Some(false));
+        for (x, y) in [(1, 2), (2, 4), (4, 6), (1, 3), (3, 5), (5, 6), (2, 5)] {
+            graph.add_edge(x, y);
+        }
+        graph.set_node_data(2, "billing");
+        graph.set_node_data(3, "search");
+        graph.set_node_data(5, "billing");
+
+        let paths = graph
+            .query(1, 6)
+            .filter_nodes(|_, team| team != Some(&"billing"))
+            .find_all_paths();
+        assert_eq!(paths, Vec::<Vec<i32>>::new());
+
+        let paths = graph
+            .query(1, 6)
+            .filter_nodes(|_, team| team != Some(&"search"))
+            .filter_nodes(|node, _| *node != 4)
+            .find_all_paths();
+        assert_eq!(paths, vec![vec![1, 2, 5, 6]]);
+
+        let only_tagged = graph
+            .query(2, 5)
+            .filter_nodes(|_, team| team.is_some())
+            .count_paths();
+        assert_eq!(only_tagged, 1);
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::ControlFlow;
+
+use crate::error::GraphError;
+use crate::frozen::FrozenGraph;
+use crate::graph::Graph;
+use crate::paths::{Constraints, PathLength, PathSearch, PathsIter, TaggedPath};
+use crate::query::PathQuery;
+use crate::shortest_paths::ShortestPaths;
+use crate::traversal::{Bfs, Dfs};
+use crate::view::View;
+use crate::weight::Weight;
+
+mod sealed {
+    use crate::view::View;
+
+    // Read access to the storage of a graph. Kept private so every searchable graph is one of
+    // this crate's own.
+    pub trait Storage<T: Eq + std::hash::Hash + Clone, E, N> {
+        fn view(&self) -> View<'_, T, E, N>;
+    }
+}
+
+// Searches available on every graph type that can be searched, Graph and FrozenGraph.
+// Each one is written once over the graph's storage; bring the trait into scope to call them.
+pub trait Search<T, E = (), N = ()>: sealed::Storage<T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    // Lazily iterate over all paths between two nodes.
+    fn paths_iter(&self, start: T, end: T) -> PathsIter<'_, T, E, N> {
+        self.view().paths_iter(start, end)
+    }
+
+    // Lazily iterate over all paths between two nodes with max steps limit.
+    fn paths_iter_with_max_steps(
+        &self,
+        start: T,
+        end: T,
+        max_steps: usize,
+    ) -> PathsIter<'_, T, E, N> {
+        self.view().paths_iter_with_max_steps(start, end, max_steps)
+    }
+
+    // Use depth-first search to find all paths between two nodes
+    fn find_all_paths(&self, start: T, end: T) -> Vec<Vec<T>> {
+        self.paths_iter(start, end).collect()
+    }
+
+    // Use depth-first search to find all paths between two nodes with max steps limit.
+    // The limit is counted in nodes, so a path of `max_steps` nodes is still accepted.
+    fn find_paths_with_max_steps(&self, start: T, end: T, max_steps: usize) -> Vec<Vec<T>> {
+        self.paths_iter_with_max_steps(start, end, max_steps)
+            .collect()
+    }
+
+    // Use depth-first search to find all paths between two nodes whose length is within bounds.
+    fn find_paths_with_length(&self, start: T, end: T, length: PathLength) -> Vec<Vec<T>> {
+        self.query(start, end).length(length).find_all_paths()
+    }
+
+    // Use depth-first search to find all paths between two nodes, each with the data of the
+    // edges it traverses.
+    fn find_all_paths_with_edges<'a>(&'a self, start: T, end: T) -> Vec<(Vec<T>, Vec<&'a E>)>
+    where
+        T: 'a,
+        N: 'a,
+    {
+        self.view().find_all_paths_with_edges(start, end)
+    }
+
+    // Get the weight of the edge between two nodes.
+    fn weight(&self, vector_x: &T, vector_y: &T) -> Option<E>
+    where
+        E: Weight,
+    {
+        self.view().weight(vector_x, vector_y)
+    }
+
+    // Sum the weights along a path. Returns None if two consecutive nodes are not connected.
+    fn path_cost(&self, path: &[T]) -> Option<E>
+    where
+        E: Weight,
+    {
+        self.view().path_cost(path)
+    }
+
+    // Use depth-first search to find all paths between two nodes, each with its total cost.
+    fn find_all_weighted_paths(&self, start: T, end: T) -> Vec<(Vec<T>, E)>
+    where
+        E: Weight,
+    {
+        self.view().find_all_weighted_paths(start, end)
+    }
+
+    // Use depth-first search to find all paths between two nodes whose total cost is at most
+    // `max_cost`, each with its total cost.
+    fn find_weighted_paths_with_max_cost(&self, start: T, end: T, max_cost: E) -> Vec<(Vec<T>, E)>
+    where
+        E: Weight,
+    {
+        self.view()
+            .find_weighted_paths_with_max_cost(start, end, max_cost)
+    }
+
+    // Find all paths from any of the sources to any of the targets, with one search per source.
+    // A path stops at the first target it reaches.
+    fn find_all_paths_between<I>(&self, sources: I, targets: &HashSet<T>) -> Vec<TaggedPath<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.view().paths_between(sources, targets, false)
+    }
+
+    // Like find_all_paths_between, but a path may pass through one target to reach another,
+    // and is reported once for every target on it.
+    fn find_all_paths_between_through_targets<I>(
+        &self,
+        sources: I,
+        targets: &HashSet<T>,
+    ) -> Vec<TaggedPath<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.view().paths_between(sources, targets, true)
+    }
+
+    // Find all paths between two nodes, exploring neighbors in ascending order.
+    // Unlike find_all_paths, the result does not depend on the order edges were added in.
+    fn find_all_paths_sorted(&self, start: T, end: T) -> Vec<Vec<T>>
+    where
+        T: Ord,
+    {
+        self.query(start, end).sorted().find_all_paths()
+    }
+
+    // Call `visitor` with each path between two nodes as it is found.
+    // The path is only borrowed, and the search stops as soon as the visitor breaks.
+    fn visit_paths<F>(&self, start: T, end: T, visitor: F) -> ControlFlow<()>
+    where
+        F: FnMut(&[T]) -> ControlFlow<()>,
+    {
+        self.query(start, end).visit_paths(visitor)
+    }
+
+    // Count all paths between two nodes without materializing them.
+    // Directed acyclic graphs are counted in linear time, other graphs fall back to depth-first search.
+    fn count_paths(&self, start: T, end: T) -> u128 {
+        self.view().count_paths(start, end)
+    }
+
+    // Count all paths between two nodes with max steps limit.
+    fn count_paths_with_max_steps(&self, start: T, end: T, max_steps: usize) -> u128 {
+        PathSearch::new(self.view(), start, end, Constraints::max_steps(max_steps)).count()
+    }
+
+    // Count all paths between two nodes with dynamic programming over a topological order.
+    // Returns None if a cycle is reachable from `start` without passing through `end`. Self-loops
+    // never lie on a path, so they do not count as cycles. Counts saturate at u128::MAX.
+    fn count_paths_dag(&self, start: T, end: T) -> Option<u128> {
+        self.view().count_paths_dag(start, end)
+    }
+
+    // Start building a constrained path search between two nodes.
+    fn query(&self, start: T, end: T) -> PathQuery<'_, T, E, N> {
+        PathQuery::new(self.view(), start, end)
+    }
+
+    // Use depth-first search on the rayon thread pool to find all paths between two nodes.
+    // Returns the same paths as find_all_paths, possibly in a different order.
+    #[cfg(feature = "parallel")]
+    fn par_find_all_paths(&self, start: T, end: T) -> Vec<Vec<T>>
+    where
+        T: Send + Sync,
+        E: Sync,
+        N: Sync,
+    {
+        self.view().par_find_all_paths(start, end)
+    }
+
+    // Iterate breadth-first over the nodes reachable from `start`, each with its depth.
+    fn bfs(&self, start: T) -> Bfs<'_, T, E, N> {
+        self.view().bfs(&start)
+    }
+
+    // Iterate depth-first over the nodes reachable from `start` in pre-order,
+    // each with its depth in the search tree.
+    fn dfs(&self, start: T) -> Dfs<'_, T, E, N> {
+        self.view().dfs(&start, false)
+    }
+
+    // Iterate depth-first over the nodes reachable from `start` in post-order,
+    // each with its depth in the search tree.
+    fn dfs_post_order(&self, start: T) -> Dfs<'_, T, E, N> {
+        self.view().dfs(&start, true)
+    }
+
+    // Use breadth-first search to find a path between two nodes with the fewest edges.
+    // Returns None if the end cannot be reached.
+    fn shortest_path(&self, start: T, end: T) -> Option<Vec<T>> {
+        self.view().shortest_path(start, end)
+    }
+
+    // Use breadth-first search to find the number of edges on a shortest path from `start`
+    // to every node it reaches, including itself.
+    fn shortest_path_lengths(&self, start: T) -> HashMap<T, usize> {
+        self.view().shortest_path_lengths(&start)
+    }
+
+    // Use Dijkstra's algorithm to find the cost of the cheapest route from `start` to every
+    // node it reaches, with the node before each one on its route.
+    // Returns an error if any edge of the graph has a negative weight.
+    fn dijkstra(&self, start: T) -> Result<ShortestPaths<T, E>, GraphError<T>>
+    where
+        E: Weight,
+    {
+        self.view().dijkstra(&start)
+    }
+
+    // Use Dijkstra's algorithm to find the cheapest path between two nodes, with its cost.
+    // Returns None if the end cannot be reached, or an error if any edge of the graph has a
+    // negative weight.
+    fn dijkstra_path(&self, start: T, end: T) -> Result<Option<(Vec<T>, E)>, GraphError<T>>
+    where
+        E: Weight,
+    {
+        self.view().cheapest_path(start, end, |_| E::zero())
+    }
+
+    // Use A* search to find the cheapest path between two nodes, with its cost. `heuristic`
+    // estimates the cost from a node to `end`; as long as it never overestimates, the path is
+    // as cheap as the one from dijkstra_path, usually after exploring far fewer nodes.
+    // Returns None if the end cannot be reached, or an error if any edge of the graph has a
+    // negative weight.
+    fn astar<H>(&self, start: T, end: T, heuristic: H) -> Result<Option<(Vec<T>, E)>, GraphError<T>>
+    where
+        E: Weight,
+        H: Fn(&T) -> E,
+    {
+        self.view().cheapest_path(start, end, heuristic)
+    }
+
+    // Use the Bellman-Ford algorithm to find the cost of the cheapest route from `start` to
+    // every node it reaches, with the node before each one on its route. Unlike dijkstra,
+    // negative weights are allowed; if a cycle with a negative total can be reached from
+    // `start`, it is returned as GraphError::NegativeCycle instead. In an undirected graph a
+    // single negative edge is such a cycle.
+    fn bellman_ford(&self, start: T) -> Result<ShortestPaths<T, E>, GraphError<T>>
+    where
+        E: Weight,
+    {
+        self.view().bellman_ford(&start)
+    }
+}
+
+impl<T, E, N> sealed::Storage<T, E, N> for Graph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    fn view(&self) -> View<'_, T, E, N> {
+        Graph::view(self)
+    }
+}
+
+impl<T, E, N> sealed::Storage<T, E, N> for FrozenGraph<T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    fn view(&self) -> View<'_, T, E, N> {
+        FrozenGraph::view(self)
+    }
+}
+
+impl<T, E, N> Search<T, E, N> for Graph<T, E, N> where T: Eq + Hash + Clone {}
+
+impl<T, E, N> Search<T, E, N> for FrozenGraph<T, E, N> where T: Eq + Hash + Clone {}
@@ -0,0 +1,584 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use crate::error::GraphError;
+use crate::graph::NodeId;
+use crate::traversal::BreadthFirst;
+use crate::view::View;
+use crate::weight::Weight;
+
+// Costs of the cheapest routes from a start node to every node it reaches,
+// with the node before each one on its route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShortestPaths<T: Eq + Hash, E> {
+    pub distances: HashMap<T, E>,
+    // Every reached node except the start.
+    pub predecessors: HashMap<T, T>,
+}
+
+impl<T, E> ShortestPaths<T, E>
+where
+    T: Eq + Hash + Clone,
+{
+    fn empty() -> Self {
+        ShortestPaths {
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+        }
+    }
+
+    // Follow the predecessors back from `end`, and return the route from the start in order.
+    // Returns None if `end` was not reached.
+    pub fn path_to(&self, end: &T) -> Option<Vec<T>> {
+        if !self.distances.contains_key(end) {
+            return None;
+        }
+        let mut path = vec![end.clone()];
+        while let Some(predecessor) = self.predecessors.get(&path[path.len() - 1]) {
+            path.push(predecessor.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// A node waiting in the priority queue with the cost of the route that reached it.
+// Ordered by `estimate` the other way round, so BinaryHeap pops the cheapest first.
+struct Queued<E> {
+    estimate: E,
+    cost: E,
+    node: NodeId,
+}
+
+impl<E: Weight> Ord for Queued<E> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl<E: Weight> PartialOrd for Queued<E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<E: Weight> PartialEq for Queued<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<E: Weight> Eq for Queued<E> {}
+
+// Result of a best-first search, indexed by node id.
+struct BestFirst<E> {
+    // Cost of the cheapest route found to each node, or None if it was not reached.
+    costs: Vec<Option<E>>,
+    parents: Vec<NodeId>,
+}
+
+impl<T, E, N> View<'_, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn shortest_path(self, start: T, end: T) -> Option<Vec<T>> {
+        if start == end {
+            return Some(vec![start]);
+        }
+        let (start, end) = (self.id(&start)?, self.id(&end)?);
+
+        let mut parents = vec![NodeId::MAX; self.id_bound()];
+        let mut search = BreadthFirst::new(self.id_bound(), [start]);
+        while let Some(reached) = search.next_with(|node| self.neighbors(node)) {
+            if let Some(parent) = reached.parent {
+                parents[reached.node as usize] = parent;
+            }
+            if reached.node == end {
+                return Some(self.trace(&parents, start, end));
+            }
+        }
+        None
+    }
+
+    // Follow the parents back from `end` to `start`, and return the nodes in order.
+    fn trace(self, parents: &[NodeId], start: NodeId, end: NodeId) -> Vec<T> {
+        let mut path = vec![self.node_at(end).clone()];
+        let mut node = end;
+        while node != start {
+            node = parents[node as usize];
+            path.push(self.node_at(node).clone());
+        }
+        path.reverse();
+        path
+    }
+
+    pub(crate) fn shortest_path_lengths(self, start: &T) -> HashMap<T, usize> {
+        let mut search = BreadthFirst::new(self.id_bound(), self.id(start));
+        let mut lengths = HashMap::new();
+        while let Some(reached) = search.next_with(|node| self.neighbors(node)) {
+            lengths.insert(self.node_at(reached.node).clone(), reached.depth);
+        }
+        lengths
+    }
+}
+
+impl<T, E, N> View<'_, T, E, N>
+where
+    T: Eq + Hash + Clone,
+    E: Weight,
+{
+    // Find the first edge with a negative weight, if there is one. Graphs that count their
+    // negative edges only need the scan to name one.
+    pub(crate) fn negative_edge(self) -> Option<(NodeId, NodeId)> {
+        if self.negative_edges() == Some(0) {
+            return None;
+        }
+        (0..self.id_bound() as NodeId).find_map(|node| {
+            self.edges_from(node)
+                .find(|(_, weight)| **weight < E::zero())
+                .map(|(neighbor, _)| (node, neighbor))
+        })
+    }
+
+    // Return an error naming the first edge with a negative weight, if there is one. Checked
+    // before a search rather than during it, as a search that stops early would miss some.
+    fn reject_negative_weights(self) -> Result<(), GraphError<T>> {
+        match self.negative_edge() {
+            Some((node, neighbor)) => Err(GraphError::NegativeWeight(
+                self.node_at(node).clone(),
+                self.node_at(neighbor).clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // Take nodes from `start` in order of their cost plus `heuristic`, stopping once `end` is
+    // taken. With a heuristic of zero this is Dijkstra's algorithm. A node is taken again if a
+    // cheaper route to it turns up later, so an admissible heuristic is enough for the route
+    // to `end` to be the cheapest.
+    fn best_first<H>(self, start: NodeId, end: Option<NodeId>, heuristic: H) -> BestFirst<E>
+    where
+        H: Fn(NodeId) -> E,
+    {
+        let mut costs = vec![None; self.id_bound()];
+        let mut parents = vec![NodeId::MAX; self.id_bound()];
+        let mut queue = BinaryHeap::new();
+        costs[start as usize] = Some(E::zero());
+        queue.push(Queued {
+            estimate: heuristic(start),
+            cost: E::zero(),
+            node: start,
+        });
+
+        while let Some(Queued { cost, node, .. }) = queue.pop() {
+            // A cheaper route to this node was queued after this one.
+            if costs[node as usize].is_some_and(|best| cost.total_cmp(&best) == Ordering::Greater) {
+                continue;
+            }
+            if Some(node) == end {
+                break;
+            }
+            for (neighbor, weight) in self.edges_from(node) {
+                let cost = cost + *weight;
+                let best = &mut costs[neighbor as usize];
+                if best.is_none_or(|best| cost.total_cmp(&best) == Ordering::Less) {
+                    *best = Some(cost);
+                    parents[neighbor as usize] = node;
+                    queue.push(Queued {
+                        estimate: cost + heuristic(neighbor),
+                        cost,
+                        node: neighbor,
+                    });
+                }
+            }
+        }
+
+        BestFirst { costs, parents }
+    }
+
+    pub(crate) fn dijkstra(self, start: &T) -> Result<ShortestPaths<T, E>, GraphError<T>> {
+        self.reject_negative_weights()?;
+        let Some(start) = self.id(start) else {
+            return Ok(ShortestPaths::empty());
+        };
+        let search = self.best_first(start, None, |_| E::zero());
+        Ok(self.shortest_paths(start, search.costs, &search.parents))
+    }
+
+    // Relax every edge out of a reached node, round after round, until no cost improves.
+    // A path without repeated nodes has fewer edges than there are nodes, so a cost still
+    // improving after that many rounds comes from a negative cycle.
+    pub(crate) fn bellman_ford(self, start: &T) -> Result<ShortestPaths<T, E>, GraphError<T>> {
+        let Some(start) = self.id(start) else {
+            return Ok(ShortestPaths::empty());
+        };
+        let mut costs = vec![None; self.id_bound()];
+        let mut parents = vec![NodeId::MAX; self.id_bound()];
+        costs[start as usize] = Some(E::zero());
+
+        let mut improved = None;
+        for _ in 0..self.id_bound() {
+            improved = None;
+            for node in 0..self.id_bound() as NodeId {
+                let Some(cost) = costs[node as usize] else {
+                    continue;
+                };
+                for (neighbor, weight) in self.edges_from(node) {
+                    let cost = cost + *weight;
+                    let best = &mut costs[neighbor as usize];
+                    if best.is_none_or(|best| cost.total_cmp(&best) == Ordering::Less) {
+                        *best = Some(cost);
+                        parents[neighbor as usize] = node;
+                        improved = Some(neighbor);
+                    }
+                }
+            }
+            if improved.is_none() {
+                break;
+            }
+        }
+
+        let Some(mut node) = improved else {
+            return Ok(self.shortest_paths(start, costs, &parents));
+        };
+        // Walking back from a node improved in the last round ends up going round the cycle.
+        for _ in 0..self.id_bound() {
+            node = parents[node as usize];
+        }
+        Err(GraphError::NegativeCycle(self.cycle(&parents, node)))
+    }
+
+    // Follow the parents back from `node` until it comes round again, and return the cycle in
+    // edge order, with `node` at both ends.
+    fn cycle(self, parents: &[NodeId], node: NodeId) -> Vec<T> {
+        let mut cycle = vec![self.node_at(node).clone()];
+        let mut current = node;
+        loop {
+            current = parents[current as usize];
+            cycle.push(self.node_at(current).clone());
+            if current == node {
+                break;
+            }
+        }
+        cycle.reverse();
+        cycle
+    }
+
+    // Collect the costs and parents found by a search from `start`, by node.
+    fn shortest_paths(
+        self,
+        start: NodeId,
+        costs: Vec<Option<E>>,
+        parents: &[NodeId],
+    ) -> ShortestPaths<T, E> {
+        let mut paths = ShortestPaths::empty();
+        for (id, cost) in costs.into_iter().enumerate() {
+            let Some(cost) = cost else {
+                continue;
+            };
+            let node = self.node_at(id as NodeId);
+            paths.distances.insert(node.clone(), cost);
+            if id as NodeId != start {
+                let parent = self.node_at(parents[id]);
+                paths.predecessors.insert(node.clone(), parent.clone());
+            }
+        }
+        paths
+    }
+
+    // Run a best-first search from `start` to `end`, and return the route found with its cost.
+    pub(crate) fn cheapest_path<H>(
+        self,
+        start: T,
+        end: T,
+        heuristic: H,
+    ) -> Result<Option<(Vec<T>, E)>, GraphError<T>>
+    where
+        H: Fn(&T) -> E,
+    {
+        self.reject_negative_weights()?;
+        if start == end {
+            return Ok(Some((vec![start], E::zero())));
+        }
+        let (Some(start), Some(end)) = (self.id(&start), self.id(&end)) else {
+            return Ok(None);
+        };
+        let search = self.best_first(start, Some(end), |id| heuristic(self.node_at(id)));
+        Ok(search.costs[end as usize].map(|cost| (self.trace(&search.parents, start, end), cost)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    use crate::fixtures::two_routes;
+    use crate::graph::Graph;
+    use crate::search::Search;
+
+    // The two routes from 1 to 6, with a tail 6-7 leading on from the end.
+    fn two_routes_with_tail(is_directed: bool) -> Graph<i32> {
+        let mut graph = two_routes(is_directed);
+        graph.add_edge(6, 7);
+        graph
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let graph = two_routes_with_tail(true);
+        let path = graph.shortest_path(1, 6).unwrap();
+        assert_eq!(path.len(), 4);
+        assert!(graph.find_all_paths(1, 6).contains(&path));
+        assert_eq!(graph.shortest_path(2, 7), Some(vec![2, 4, 6, 7]));
+        assert_eq!(graph.shortest_path(6, 1), None);
+        assert_eq!(graph.shortest_path(1, 8), None);
+        assert_eq!(graph.shortest_path(8, 8), Some(vec![8]));
+
+        let graph = two_routes_with_tail(false);
+        assert_eq!(graph.shortest_path(7, 3), Some(vec![7, 6, 5, 3]));
+        assert_eq!(
+            graph.clone().freeze().shortest_path(7, 3),
+            Some(vec![7, 6, 5, 3])
+        );
+    }
+
+    //  A --1-- B --1-- C
+    //  |               |
+    //  5 ----- D --1---+
+    fn weighted_graph<E: Weight>(weights: [E; 5]) -> Graph<&'static str, E> {
+        let mut graph = Graph::new_weighted(Some(false));
+        let [ab, bc, ad, cd, de] = weights;
+        graph.add_weighted_edge("A", "B", ab);
+        graph.add_weighted_edge("B", "C", bc);
+        graph.add_weighted_edge("A", "D", ad);
+        graph.add_weighted_edge("C", "D", cd);
+        graph.add_weighted_edge("D", "E", de);
+        graph
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+    struct Minutes(u32);
+
+    impl std::ops::Add for Minutes {
+        type Output = Self;
+
+        fn add(self, other: Self) -> Self {
+            Minutes(self.0 + other.0)
+        }
+    }
+
+    impl Weight for Minutes {
+        fn zero() -> Self {
+            Minutes(0)
+        }
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let graph = weighted_graph([1, 1, 5, 1, 2]);
+        let paths = graph.dijkstra("A").unwrap();
+        let expected = HashMap::from([("A", 0), ("B", 1), ("C", 2), ("D", 3), ("E", 5)]);
+        assert_eq!(paths.distances, expected);
+        assert_eq!(paths.predecessors["D"], "C");
+        assert!(!paths.predecessors.contains_key("A"));
+        assert_eq!(paths.path_to(&"E"), Some(vec!["A", "B", "C", "D", "E"]));
+        assert_eq!(paths.path_to(&"F"), None);
+        assert_eq!(
+            graph.dijkstra_path("E", "A").unwrap(),
+            Some((vec!["E", "D", "C", "B", "A"], 5))
+        );
+        assert_eq!(graph.dijkstra_path("A", "F").unwrap(), None);
+        assert!(graph.dijkstra("F").unwrap().distances.is_empty());
+
+        let graph = weighted_graph([0.5, 2.5, 2.0, 0.25, 1.0]).freeze();
+        assert_eq!(
+            graph.dijkstra_path("A", "C").unwrap(),
+            Some((vec!["A", "D", "C"], 2.25))
+        );
+
+        let graph = weighted_graph([1, 2, 3, 4, 5].map(Minutes));
+        assert_eq!(graph.dijkstra("A").unwrap().distances["E"], Minutes(8));
+    }
+
+    // The cheapest route from 1 to 2 goes through 3, but a search that stops on taking 2 would
+    // never take 3, which costs more to reach.
+    fn negative_shortcut() -> Graph<i32, i32> {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_weighted_edge(1, 2, 4);
+        graph.add_weighted_edge(1, 3, 5);
+        graph.add_weighted_edge(3, 2, -3);
+        graph
+    }
+
+    #[test]
+    fn test_dijkstra_negative_weight() {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_weighted_edge(1, 2, 4);
+        graph.add_weighted_edge(2, 3, -1);
+        graph.add_weighted_edge(4, 1, -2);
+        let error = graph.dijkstra(1).unwrap_err();
+        assert_eq!(error, GraphError::NegativeWeight(2, 3));
+        assert_eq!(error.to_string(), "edge from 2 to 3 has a negative weight");
+        // Every edge is checked, not only those the search would follow.
+        assert_eq!(
+            graph.dijkstra_path(3, 3),
+            Err(GraphError::NegativeWeight(2, 3))
+        );
+        assert_eq!(graph.dijkstra(5), Err(GraphError::NegativeWeight(2, 3)));
+
+        // Edges added without add_weighted_edge are not counted, but still checked.
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_edge_with(1, 2, -1);
+        assert_eq!(graph.dijkstra(1), Err(GraphError::NegativeWeight(1, 2)));
+
+        let graph = negative_shortcut();
+        assert_eq!(graph.dijkstra(1), Err(GraphError::NegativeWeight(3, 2)));
+        assert_eq!(
+            graph.dijkstra_path(1, 2),
+            Err(GraphError::NegativeWeight(3, 2))
+        );
+    }
+
+    #[test]
+    fn test_bellman_ford() {
+        let mut graph = Graph::new_weighted(Some(true));
+        graph.add_weighted_edge(1, 2, 4);
+        graph.add_weighted_edge(1, 3, 5);
+        graph.add_weighted_edge(3, 2, -3);
+        graph.add_weighted_edge(2, 4, 1);
+        graph.add_weighted_edge(5, 5, -1);
+        let paths = graph.bellman_ford(1).unwrap();
+        let expected = HashMap::from([(1, 0), (2, 2), (3, 5), (4, 3)]);
+        assert_eq!(paths.distances, expected);
+        assert_eq!(paths.path_to(&4), Some(vec![1, 3, 2, 4]));
+        assert_eq!(
+            graph.bellman_ford(5),
+            Err(GraphError::NegativeCycle(vec![5, 5]))
+        );
+        assert!(graph.bellman_ford(6).unwrap().distances.is_empty());
+
+        let mut graph = Graph::new_weighted(Some(false));
+        graph.add_weighted_edge("A", "B", 2);
+        graph.add_weighted_edge("B", "C", -1);
+        let Err(GraphError::NegativeCycle(cycle)) = graph.freeze().bellman_ford("A") else {
+            panic!("expected a negative cycle");
+        };
+        assert!(cycle == ["B", "C", "B"] || cycle == ["C", "B", "C"]);
+    }
+
+    #[test]
+    fn test_bellman_ford_arbitrage() {
+        // Converting along a cycle whose rates multiply to more than 1 is a profit,
+        // which shows up as a negative cycle once each rate is weighted by -ln(rate).
+        let rates = [
+            ("USD", "EUR", 0.9),
+            ("EUR", "GBP", 0.9),
+            ("GBP", "USD", 1.3),
+            ("USD", "JPY", 150.0),
+            ("JPY", "EUR", 0.006),
+        ];
+        let mut graph = Graph::new_weighted(Some(true));
+        for (from, to, rate) in rates {
+            graph.add_weighted_edge(from, to, -f64::ln(rate));
+        }
+        let Err(GraphError::NegativeCycle(cycle)) = graph.bellman_ford("JPY") else {
+            panic!("expected a negative cycle");
+        };
+        assert_eq!(cycle.len(), 4);
+        assert_eq!(cycle[0], cycle[3]);
+        assert!(graph.path_cost(&cycle).unwrap() < 0.0);
+        for currency in ["USD", "EUR", "GBP"] {
+            assert!(cycle.contains(&currency));
+        }
+
+        graph.add_weighted_edge("GBP", "USD", -f64::ln(1.2));
+        let paths = graph.bellman_ford("JPY").unwrap();
+        assert_eq!(
+            paths.path_to(&"USD"),
+            Some(vec!["JPY", "EUR", "GBP", "USD"])
+        );
+        assert!(paths.distances["USD"] > 0.0);
+    }
+
+    // A size x size grid of unit-weight edges, with a wall along column 4 open only at the top.
+    fn walled_grid(size: i32) -> Graph<(i32, i32), i32> {
+        let mut graph = Graph::new_weighted(Some(false));
+        let wall = |(x, y): (i32, i32)| x == 4 && y > 0;
+        for x in 0..size {
+            for y in 0..size {
+                for next in [(x + 1, y), (x, y + 1)] {
+                    if next.0 < size && next.1 < size && !wall((x, y)) && !wall(next) {
+                        graph.add_weighted_edge((x, y), next, 1);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_astar() {
+        let graph = walled_grid(12);
+        let (start, end) = ((0, 6), (11, 6));
+        let manhattan = |(x, y): &(i32, i32)| (end.0 - x).abs() + (end.1 - y).abs();
+
+        let (path, cost) = graph.astar(start, end, manhattan).unwrap().unwrap();
+        assert_eq!(cost, 23);
+        assert_eq!(graph.path_cost(&path), Some(cost));
+        assert!(path.contains(&(4, 0)));
+        let (_, dijkstra_cost) = graph.dijkstra_path(start, end).unwrap().unwrap();
+        assert_eq!(cost, dijkstra_cost);
+
+        // The heuristic is asked once for every node queued, so count the calls to compare how
+        // much of the grid each search explores.
+        let queued = |heuristic: &dyn Fn(&(i32, i32)) -> i32| {
+            let calls = Cell::new(0);
+            graph
+                .astar(start, end, |node| {
+                    calls.set(calls.get() + 1);
+                    heuristic(node)
+                })
+                .unwrap();
+            calls.get()
+        };
+        assert!(queued(&manhattan) < queued(&|_| 0));
+
+        let frozen = graph.clone().freeze();
+        assert_eq!(
+            frozen
+                .astar((0, 0), (3, 3), |_| 0)
+                .unwrap()
+                .map(|(_, cost)| cost),
+            Some(6)
+        );
+        assert_eq!(graph.astar((0, 0), (20, 20), |_| 0), Ok(None));
+    }
+
+    #[test]
+    fn test_astar_negative_weight() {
+        let graph = negative_shortcut();
+        assert_eq!(
+            graph.astar(1, 2, |_| 0),
+            Err(GraphError::NegativeWeight(3, 2))
+        );
+        assert_eq!(
+            graph.freeze().astar(1, 1, |_| 0),
+            Err(GraphError::NegativeWeight(3, 2))
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_lengths() {
+        let graph = two_routes_with_tail(true);
+        let lengths = graph.shortest_path_lengths(2);
+        let expected = HashMap::from([(2, 0), (4, 1), (5, 1), (6, 2), (7, 3)]);
+        assert_eq!(lengths, expected);
+        assert!(graph.shortest_path_lengths(8).is_empty());
+
+        let graph = two_routes_with_tail(false).freeze();
+        assert_eq!(graph.shortest_path_lengths(7)[&1], 4);
+        assert_eq!(graph.shortest_path_lengths(7).len(), 7);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use crate::bitset::BitSet;
+use crate::graph::NodeId;
+use crate::view::{Neighbors, View};
+
+// Breadth-first search over node ids, shared by the public iterators and the crate's own
+// algorithms. The edges to follow are given on every step, so the same search can also run
+// over reversed edges.
+pub(crate) struct BreadthFirst {
+    queue: VecDeque<Reached>,
+    visited: BitSet,
+    // Nodes at this depth are yielded but their successors are not queued.
+    max_depth: Option<usize>,
+}
+
+// A node taken from the breadth-first queue.
+pub(crate) struct Reached {
+    pub(crate) node: NodeId,
+    // Number of edges from the nearest source.
+    pub(crate) depth: usize,
+    // Node it was first reached from, or None for a source.
+    pub(crate) parent: Option<NodeId>,
+}
+
+impl BreadthFirst {
+    // Start from every one of `sources` at depth 0.
+    pub(crate) fn new<I: IntoIterator<Item = NodeId>>(id_bound: usize, sources: I) -> Self {
+        let mut visited = BitSet::new(id_bound);
+        let queue = sources
+            .into_iter()
+            .filter(|source| visited.insert(*source))
+            .map(|node| Reached {
+                node,
+                depth: 0,
+                parent: None,
+            })
+            .collect();
+        BreadthFirst {
+            queue,
+            visited,
+            max_depth: None,
+        }
+    }
+
+    // Stop the search at `max_depth` edges from the sources, if given.
+    pub(crate) fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Take the next node, and queue the successors not seen yet.
+    pub(crate) fn next_with<I, F>(&mut self, successors: F) -> Option<Reached>
+    where
+        I: IntoIterator<Item = NodeId>,
+        F: FnOnce(NodeId) -> I,
+    {
+        let reached = self.queue.pop_front()?;
+        if self.max_depth == Some(reached.depth) {
+            return Some(reached);
+        }
+        for successor in successors(reached.node) {
+            if self.visited.insert(successor) {
+                self.queue.push_back(Reached {
+                    node: successor,
+                    depth: reached.depth + 1,
+                    parent: Some(reached.node),
+                });
+            }
+        }
+        Some(reached)
+    }
+}
+
+// Breadth-first traversal from a start node. Every reachable node is yielded once, with its
+// depth: the number of edges on a shortest path from the start.
+pub struct Bfs<'a, T: Eq + Hash + Clone, E = (), N = ()> {
+    graph: View<'a, T, E, N>,
+    search: BreadthFirst,
+}
+
+impl<'a, T, E, N> Iterator for Bfs<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let graph = self.graph;
+        let reached = self.search.next_with(|node| graph.neighbors(node))?;
+        Some((graph.node_at(reached.node), reached.depth))
+    }
+}
+
+// Depth-first traversal from a start node. Every reachable node is yielded once, with its
+// depth in the search tree, either when it is first reached (pre-order) or once all of its
+// descendants are done (post-order).
+pub struct Dfs<'a, T: Eq + Hash + Clone, E = (), N = ()> {
+    graph: View<'a, T, E, N>,
+    post_order: bool,
+    // Taken on the first call to next.
+    start: Option<NodeId>,
+    // Nodes of the current branch, each with the neighbors left to explore.
+    stack: Vec<(NodeId, Neighbors<'a, E>)>,
+    visited: BitSet,
+}
+
+impl<'a, T, E, N> Dfs<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    // Enter `node` and return it if it is yielded on entry.
+    fn enter(&mut self, node: NodeId) -> Option<(&'a T, usize)> {
+        let depth = self.stack.len();
+        self.visited.insert(node);
+        self.stack.push((node, self.graph.neighbors(node)));
+        (!self.post_order).then(|| (self.graph.node_at(node), depth))
+    }
+}
+
+impl<'a, T, E, N> Iterator for Dfs<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            if let Some(visit) = self.enter(start) {
+                return Some(visit);
+            }
+        }
+
+        while let Some((node, neighbors)) = self.stack.last_mut() {
+            let visited = &self.visited;
+            match neighbors.find(|neighbor| !visited.contains(*neighbor)) {
+                Some(neighbor) => {
+                    if let Some(visit) = self.enter(neighbor) {
+                        return Some(visit);
+                    }
+                }
+                None => {
+                    let node = *node;
+                    self.stack.pop();
+                    if self.post_order {
+                        return Some((self.graph.node_at(node), self.stack.len()));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, T, E, N> View<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn bfs(self, start: &T) -> Bfs<'a, T, E, N> {
+        Bfs {
+            graph: self,
+            search: BreadthFirst::new(self.id_bound(), self.id(start)),
+        }
+    }
+
+    pub(crate) fn dfs(self, start: &T, post_order: bool) -> Dfs<'a, T, E, N> {
+        Dfs {
+            graph: self,
+            post_order,
+            start: self.id(start),
+            stack: Vec::new(),
+            visited: BitSet::new(self.id_bound()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::search::Search;
+
+    //     1
+    //    / \
+    //   2   3
+    //  / \   \
+    // 4   5 - 6
+    fn tree_with_cross_edge(is_directed: bool) -> Graph<i32> {
+        let mut graph = Graph::new(Some(is_directed));
+        for (x, y) in [(1, 2), (1, 3), (2, 4), (2, 5), (3, 6), (5, 6)] {
+            graph.add_edge(x, y);
+        }
+        graph
+    }
+
+    fn owned<'a>(visits: impl Iterator<Item = (&'a i32, usize)>) -> Vec<(i32, usize)> {
+        visits.map(|(node, depth)| (*node, depth)).collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let graph = tree_with_cross_edge(true);
+        assert_eq!(
+            owned(graph.bfs(1)),
+            vec![(1, 0), (2, 1), (3, 1), (4, 2), (5, 2), (6, 2)]
+        );
+        assert_eq!(owned(graph.bfs(5)), vec![(5, 0), (6, 1)]);
+        assert_eq!(graph.bfs(7).count(), 0);
+
+        let graph = tree_with_cross_edge(false);
+        assert_eq!(
+            owned(graph.bfs(6)),
+            vec![(6, 0), (3, 1), (5, 1), (1, 2), (2, 2), (4, 3)]
+        );
+    }
+
+    #[test]
+    fn test_dfs() {
+        let graph = tree_with_cross_edge(true);
+        assert_eq!(
+            owned(graph.dfs(1)),
+            vec![(1, 0), (2, 1), (4, 2), (5, 2), (6, 3), (3, 1)]
+        );
+        assert_eq!(
+            owned(graph.dfs_post_order(1)),
+            vec![(4, 2), (6, 3), (5, 2), (2, 1), (3, 1), (1, 0)]
+        );
+        assert_eq!(graph.dfs_post_order(7).count(), 0);
+
+        // A cycle back to the start does not revisit it.
+        let mut graph = Graph::new(Some(true));
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        graph.add_edge(2, 2);
+        let frozen = graph.clone().freeze();
+        assert_eq!(graph.dfs(2).collect::<Vec<_>>(), vec![(&2, 0), (&1, 1)]);
+        assert_eq!(
+            frozen.dfs_post_order(2).collect::<Vec<_>>(),
+            vec![(&1, 1), (&2, 0)]
+        );
+        assert_eq!(frozen.bfs(1).collect::<Vec<_>>(), vec![(&1, 0), (&2, 1)]);
+    }
+}
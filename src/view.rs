@@ -0,0 +1,150 @@
+use std::hash::Hash;
+use std::iter::Zip;
+use std::slice;
+
+use crate::frozen::FrozenGraph;
+use crate::graph::{Graph, NodeId};
+
+// Read access to either kind of graph storage, so the searches are written once for both.
+// Public only because the sealed Search trait hands it out; the module keeps it unnameable
+// outside the crate.
+pub enum View<'a, T: Eq + Hash + Clone, E, N> {
+    Hashed(&'a Graph<T, E, N>),
+    Frozen(&'a FrozenGraph<T, E, N>),
+}
+
+impl<T, E, N> Clone for View<'_, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, E, N> Copy for View<'_, T, E, N> where T: Eq + Hash + Clone {}
+
+impl<'a, T, E, N> View<'a, T, E, N>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn is_directed(self) -> bool {
+        match self {
+            View::Hashed(graph) => graph.is_directed,
+            View::Frozen(graph) => graph.is_directed,
+        }
+    }
+
+    pub(crate) fn id(self, node: &T) -> Option<NodeId> {
+        match self {
+            View::Hashed(graph) => graph.id(node),
+            View::Frozen(graph) => graph.id(node),
+        }
+    }
+
+    pub(crate) fn node_at(self, id: NodeId) -> &'a T {
+        match self {
+            View::Hashed(graph) => graph.node_at(id),
+            View::Frozen(graph) => &graph.nodes[id as usize],
+        }
+    }
+
+    pub(crate) fn node_data_at(self, id: NodeId) -> Option<&'a N> {
+        match self {
+            View::Hashed(graph) => graph.node_data_at(id),
+            View::Frozen(graph) => graph.node_data[id as usize].as_ref(),
+        }
+    }
+
+    // Upper bound on node ids, for sizing tables indexed by id.
+    pub(crate) fn id_bound(self) -> usize {
+        match self {
+            View::Hashed(graph) => graph.id_bound(),
+            View::Frozen(graph) => graph.nodes.len(),
+        }
+    }
+
+    // Iterate over the ids of the nodes with an edge from `id`.
+    pub(crate) fn neighbors(self, id: NodeId) -> Neighbors<'a, E> {
+        match self {
+            View::Hashed(graph) => Neighbors::Hashed(graph.adjacency_list[id as usize].iter()),
+            View::Frozen(graph) => Neighbors::Frozen(graph.neighbor_ids(id).iter()),
+        }
+    }
+
+    // Iterate over the ids of the nodes with an edge from `id`, each with the edge's data.
+    pub(crate) fn edges_from(self, id: NodeId) -> EdgesFrom<'a, E> {
+        match self {
+            View::Hashed(graph) => EdgesFrom::Hashed(graph.adjacency_list[id as usize].iter()),
+            View::Frozen(graph) => {
+                EdgesFrom::Frozen(graph.neighbor_ids(id).iter().zip(graph.edge_data_from(id)))
+            }
+        }
+    }
+
+    // Get the data of the edge between two nodes.
+    pub(crate) fn edge(self, vector_x: &T, vector_y: &T) -> Option<&'a E> {
+        match self {
+            View::Hashed(graph) => graph.edge(vector_x, vector_y),
+            View::Frozen(graph) => graph.edge(vector_x, vector_y),
+        }
+    }
+
+    // Count the stored edge directions with a negative weight, if the graph keeps count.
+    pub(crate) fn negative_edges(self) -> Option<usize> {
+        match self {
+            View::Hashed(graph) => graph.negative_edges(),
+            View::Frozen(graph) => graph.negative_edges,
+        }
+    }
+
+    // Check whether incoming edges can be listed without a scan over every edge.
+    pub(crate) fn has_reverse_index(self) -> bool {
+        match self {
+            View::Hashed(graph) => graph.has_reverse_index(),
+            View::Frozen(_) => true,
+        }
+    }
+
+    // Iterate over the ids of the nodes with an edge to `id`.
+    pub(crate) fn predecessors(self, id: NodeId) -> Box<dyn Iterator<Item = NodeId> + 'a> {
+        match self {
+            View::Hashed(graph) => graph.predecessor_ids(id),
+            View::Frozen(graph) => Box::new(graph.predecessor_ids(id).iter().copied()),
+        }
+    }
+}
+
+// Iterator over the ids of the neighbors of a node.
+pub(crate) enum Neighbors<'a, E> {
+    Hashed(slice::Iter<'a, (NodeId, E)>),
+    Frozen(slice::Iter<'a, NodeId>),
+}
+
+impl<E> Iterator for Neighbors<'_, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        match self {
+            Neighbors::Hashed(neighbors) => neighbors.next().map(|(neighbor, _)| *neighbor),
+            Neighbors::Frozen(neighbors) => neighbors.next().copied(),
+        }
+    }
+}
+
+// Iterator over the neighbors of a node, each with the data of the edge leading to it.
+pub(crate) enum EdgesFrom<'a, E> {
+    Hashed(slice::Iter<'a, (NodeId, E)>),
+    Frozen(Zip<slice::Iter<'a, NodeId>, slice::Iter<'a, E>>),
+}
+
+impl<'a, E> Iterator for EdgesFrom<'a, E> {
+    type Item = (NodeId, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            EdgesFrom::Hashed(edges) => edges.next().map(|(neighbor, data)| (*neighbor, data)),
+            EdgesFrom::Frozen(edges) => edges.next().map(|(neighbor, data)| (*neighbor, data)),
+        }
+    }
+}
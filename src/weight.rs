@@ -0,0 +1,45 @@
+use std::cmp::Ordering;
+use std::ops::Add;
+
+// Edge weights: costs that start from zero, add up along a path and can be compared.
+pub trait Weight: Copy + PartialOrd + Add<Output = Self> {
+    fn zero() -> Self;
+
+    // Order two weights for searches that keep costs in a priority queue.
+    // Floats override this with their total ordering, so NaN does not need a special case.
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other)
+            .expect("weights must be totally ordered")
+    }
+}
+
+macro_rules! impl_weight {
+    ($($t:ty),*) => {
+        $(
+            impl Weight for $t {
+                fn zero() -> Self {
+                    0 as $t
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_float_weight {
+    ($($t:ty),*) => {
+        $(
+            impl Weight for $t {
+                fn zero() -> Self {
+                    0.0
+                }
+
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    <$t>::total_cmp(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_weight!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_float_weight!(f32, f64);